@@ -1,5 +1,7 @@
 use crate::core::color::Rgb;
 use crate::core::color_names::ColorNames;
+use crate::core::css_color::{ColorFormat, CssColor};
+use crate::core::gamut::{Gamut, WideColor};
 use crate::{core::color_detail::ColorDetail, widgets::custom_color_edit_button_srgba};
 use eframe::egui;
 use serde::{Deserialize, Serialize};
@@ -7,6 +9,12 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize)]
 pub struct ColorApp {
     color: ColorDetail,
+    #[serde(default)]
+    gamut: Gamut,
+    #[serde(default)]
+    copy_format: ColorFormat,
+    #[serde(skip_serializing, skip_deserializing)]
+    css_input: String,
 }
 
 const PALETTE_BUTTON_SIZE: egui::Vec2 = egui::vec2(100., 100.);
@@ -14,6 +22,9 @@ impl Default for ColorApp {
     fn default() -> Self {
         Self {
             color: ColorDetail::default(),
+            gamut: Gamut::default(),
+            copy_format: ColorFormat::default(),
+            css_input: String::new(),
         }
     }
 }
@@ -35,12 +46,16 @@ impl ColorApp {
     }
     fn color_options_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         custom_color_edit_button_srgba(ui, &mut self.color.egui_color);
+        // Before the wide-gamut values are shown, so they follow an sRGB edit
+        self.color.update_from_egui_color(true);
+        self.wide_gamut_controls(ui, ctx);
+        self.text_format_controls(ui);
         ui.vertical(|ui| {
             ui.vertical(|ui| {
                 ui.label("Color");
                 self.color.update_from_egui_color(true);
                 if Self::color_button(ui, self.color.egui_color, &self.color.hex).clicked() {
-                    ctx.copy_text(self.color.hex.to_owned());
+                    ctx.copy_text(self.copy_format.format(self.color.color));
                 }
                 self.color_info(ui, &self.color.color);
             });
@@ -66,7 +81,7 @@ impl ColorApp {
                     )
                     .clicked()
                     {
-                        ctx.copy_text(self.color.complement_hex.to_owned());
+                        ctx.copy_text(self.copy_format.format(self.color.complement));
                     }
                     self.color_info(ui, &self.color.complement);
                 });
@@ -82,7 +97,7 @@ impl ColorApp {
                     )
                     .clicked()
                     {
-                        ctx.copy_text(self.color.split_complement_hex.0.to_owned());
+                        ctx.copy_text(self.copy_format.format(self.color.split_complement.0));
                     }
                     self.color_info(ui, &self.color.split_complement.0);
                 });
@@ -94,7 +109,7 @@ impl ColorApp {
                     )
                     .clicked()
                     {
-                        ctx.copy_text(self.color.split_complement_hex.1.to_owned());
+                        ctx.copy_text(self.copy_format.format(self.color.split_complement.1));
                     }
                     self.color_info(ui, &self.color.split_complement.1);
                 });
@@ -102,6 +117,76 @@ impl ColorApp {
         });
     }
 
+    /// Copy format for the swatches, and a field taking any CSS color.
+    fn text_format_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Copy as")
+                .selected_text(self.copy_format.label())
+                .show_ui(ui, |ui| {
+                    for format in ColorFormat::ALL {
+                        ui.selectable_value(&mut self.copy_format, format, format.label());
+                    }
+                });
+            ui.monospace(self.copy_format.format(self.color.color));
+        });
+        ui.horizontal(|ui| {
+            let edit = ui.add(
+                egui::TextEdit::singleline(&mut self.css_input)
+                    .hint_text("oklch(0.7 0.15 40), #f80, tomato…"),
+            );
+            let parsed = CssColor::parse(&self.css_input);
+            let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let set = ui
+                .add_enabled(parsed.is_some(), egui::Button::new("Set"))
+                .clicked();
+            if (set || submitted)
+                && let Some(c) = parsed
+            {
+                self.color.egui_color = egui::Color32::from_rgb(c[0], c[1], c[2]);
+            }
+        });
+    }
+
+    fn wide_gamut_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.label("Gamut");
+            for gamut in Gamut::ALL {
+                ui.selectable_value(&mut self.gamut, gamut, gamut.label());
+            }
+        });
+        if self.gamut == Gamut::Srgb {
+            return;
+        }
+        let mut wide = match self.color.wide {
+            Some(w) => w.convert(self.gamut),
+            None => WideColor::from_srgb(self.color.color, self.gamut),
+        };
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for (label, v) in ["R", "G", "B"].iter().zip(wide.coords.iter_mut()) {
+                ui.label(*label);
+                changed |= ui
+                    .add(egui::DragValue::new(v).speed(0.002).range(0.0..=1.0))
+                    .changed();
+            }
+        });
+        if changed {
+            self.color.set_wide(wide);
+        }
+        ui.horizontal(|ui| {
+            ui.monospace(wide.css());
+            if ui.button("Copy CSS").clicked() {
+                ctx.copy_text(wide.css());
+            }
+        });
+        if self.color.out_of_srgb() {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Outside sRGB: swatches show the clipped color, CSS keeps the wide-gamut value",
+            );
+        }
+    }
+
     fn color_info(&self, ui: &mut egui::Ui, color: &Rgb<u8>) {
         let c_name = ColorNames::get_color_name(color);
         if let Some(name) = c_name {
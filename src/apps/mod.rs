@@ -12,3 +12,6 @@ pub use color_wheel_app::*;
 
 pub mod paint_app;
 pub use paint_app::*;
+
+pub mod recolor_app;
+pub use recolor_app::*;
@@ -4,18 +4,39 @@ use std::error::Error;
 use std::fs::{self, File};
 use std::io::Write;
 
-use egui::{ColorImage, UserData, ViewportCommand};
-use image::{DynamicImage, RgbaImage};
+use egui::ColorImage;
+use image::{DynamicImage, GenericImageView, RgbaImage};
 
 use crate::{
     core::{
+        adjustments::{Adjustment, AdjustmentStack},
         color::{ColorUtil, Rgb},
         color_detail::ColorDetail,
         color_names::ColorNames,
-        palette::Palette,
+        color_space::{ColorManagement, SourceColorSpace},
+        coverage::Coverage,
+        css_color::ColorFormat,
+        eyedropper::{Eyedropper, SampleSize},
+        gamut::Gamut,
+        highlight::Highlight,
+        lut::{CUBE_SIZES, Lut, LutMapping, LutSettings, TextureOrientation},
+        palette::{
+            CodeTemplate, PALETTE_FORMATS, Palette, ROLES, StyleFormat, TEMPLATE_DIR,
+            TEXT_EXTENSIONS, TokenNaming, TokenSettings,
+        },
+        path_sampler::PathSampler,
+        pixelate::{BlockMode, PixelGrid, Pixelate, PixelateSettings},
+        saliency::{MaskBrush, Saliency, SaliencySettings, WeightGrid},
+        segmentation::{Segment, SegmentSettings, Segmentation},
         similar::Similar,
+        style_guide::StyleGuide,
+        swatch_sheet::{SheetPreset, SheetSettings, SwatchSheet},
+        terminal_theme::{TerminalFormat, TerminalTheme},
+    },
+    widgets::{
+        custom_color_edit_button_srgba,
+        image_viewer::{ImageViewer, ImageViewerResponse, viewer_texture_options},
     },
-    widgets::custom_color_edit_button_srgba,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -31,6 +52,16 @@ enum SourceFileState {
     File,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+enum ImageView {
+    #[default]
+    Source,
+    Adjust,
+    Pixelate,
+    Segments,
+    Saliency,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PaletteApp {
     app_state: AppState,
@@ -47,9 +78,113 @@ pub struct PaletteApp {
     color_picking: bool,
     last_color_picked: Option<Rgb<u8>>,
     palette_list: Vec<Palette>,
+    #[serde(skip_serializing, skip_deserializing)]
+    source_image: Option<DynamicImage>,
+    #[serde(skip_serializing, skip_deserializing)]
+    source_color_space: Option<SourceColorSpace>,
+    #[serde(default)]
+    style_format: StyleFormat,
+    #[serde(default)]
+    token_settings: TokenSettings,
+    /// Name of the selected code template.
+    #[serde(default)]
+    code_template: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    code_templates: Vec<CodeTemplate>,
+    #[serde(default)]
+    image_view: ImageView,
+    #[serde(default)]
+    pixelate_settings: PixelateSettings,
+    #[serde(skip_serializing, skip_deserializing)]
+    pixelated: Option<(RgbaImage, egui::TextureHandle)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    hovered_color: Option<Rgb<u8>>,
+    #[serde(default = "default_highlight_delta_e")]
+    highlight_delta_e: f32,
+    #[serde(skip_serializing, skip_deserializing)]
+    highlight: Option<(Highlight, egui::TextureHandle)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    coverage: Option<(Vec<Rgb<u8>>, Vec<f32>)>,
+    #[serde(default)]
+    show_heatmap: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    heatmap: Option<(Vec<Rgb<u8>>, f32, egui::TextureHandle)>,
+    #[serde(default)]
+    sample_size: SampleSize,
+    #[serde(default)]
+    image_viewer: ImageViewer,
+    #[serde(default)]
+    path_tool: bool,
+    #[serde(default)]
+    path_sampler: PathSampler,
+    #[serde(default)]
+    adjustments: AdjustmentStack,
+    #[serde(skip_serializing, skip_deserializing)]
+    adjusted: Option<(AdjustmentStack, DynamicImage, egui::TextureHandle)>,
+    #[serde(default)]
+    adjust_viewer: ImageViewer,
+    #[serde(default)]
+    segment_settings: SegmentSettings,
+    #[serde(default)]
+    segment_viewer: ImageViewer,
+    #[serde(skip_serializing, skip_deserializing)]
+    segmentation: Option<Segmentation>,
+    #[serde(skip_serializing, skip_deserializing)]
+    selected_segments: Vec<usize>,
+    /// Overlay texture with the selection and hovered segment it was drawn for.
+    #[serde(skip_serializing, skip_deserializing)]
+    segment_overlay: Option<(Vec<usize>, Option<usize>, egui::TextureHandle)>,
+    #[serde(default)]
+    saliency_settings: SaliencySettings,
+    #[serde(default)]
+    saliency_brush: MaskBrush,
+    #[serde(default)]
+    saliency_viewer: ImageViewer,
+    #[serde(skip_serializing, skip_deserializing)]
+    saliency_mask: Option<WeightGrid>,
+    /// Weights with the settings they were computed for, `None` after the mask changes.
+    #[serde(skip_serializing, skip_deserializing)]
+    saliency_weights: Option<(SaliencySettings, WeightGrid, egui::TextureHandle)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    paste_text: String,
+    #[serde(default)]
+    copy_format: ColorFormat,
+    #[serde(default)]
+    sheet_settings: SheetSettings,
+    #[serde(skip_serializing, skip_deserializing)]
+    show_sheet_dialog: bool,
+    #[serde(default)]
+    terminal_format: TerminalFormat,
+    #[serde(default)]
+    lut_settings: LutSettings,
+    /// Preview with the settings and colors it was rendered for.
+    #[serde(skip_serializing, skip_deserializing)]
+    sheet_preview: Option<(SheetSettings, Vec<Rgb<u8>>, egui::TextureHandle)>,
+    /// Code export text with the format, settings and palette it was built from.
+    #[serde(skip_serializing, skip_deserializing)]
+    style_text: Option<(StyleFormat, TokenSettings, ExportInputs, String)>,
+    /// Code snippet text with the template and palette it was rendered from.
+    #[serde(skip_serializing, skip_deserializing)]
+    snippet_text: Option<(CodeTemplate, ExportInputs, String)>,
+}
+
+/// Palette colors, imported names, roles and palette name, everything the
+/// text exports read from the palette.
+type ExportInputs = (
+    Vec<Rgb<u8>>,
+    Vec<(Rgb<u8>, String)>,
+    Vec<(Rgb<u8>, String)>,
+    String,
+);
+
+fn default_highlight_delta_e() -> f32 {
+    10.
 }
 
 const PALETTE_BUTTON_SIZE: egui::Vec2 = egui::vec2(100., 100.);
+/// Pixels per side shown in the eyedropper loupe, odd so there is a center.
+const LOUPE_PIXELS: i32 = 11;
+const LOUPE_CELL: f32 = 12.;
 impl Default for PaletteApp {
     fn default() -> Self {
         let save_res = Self::load_palette_list();
@@ -74,6 +209,47 @@ impl Default for PaletteApp {
             color_picking: false,
             last_color_picked: None,
             palette_list,
+            source_image: None,
+            source_color_space: None,
+            style_format: StyleFormat::default(),
+            token_settings: TokenSettings::default(),
+            code_template: String::new(),
+            code_templates: Vec::new(),
+            image_view: ImageView::default(),
+            pixelate_settings: PixelateSettings::default(),
+            pixelated: None,
+            hovered_color: None,
+            highlight_delta_e: default_highlight_delta_e(),
+            highlight: None,
+            coverage: None,
+            show_heatmap: false,
+            heatmap: None,
+            sample_size: SampleSize::default(),
+            image_viewer: ImageViewer::default(),
+            path_tool: false,
+            path_sampler: PathSampler::default(),
+            adjustments: AdjustmentStack::default(),
+            adjusted: None,
+            adjust_viewer: ImageViewer::default(),
+            segment_settings: SegmentSettings::default(),
+            segment_viewer: ImageViewer::default(),
+            segmentation: None,
+            selected_segments: Vec::new(),
+            segment_overlay: None,
+            saliency_settings: SaliencySettings::default(),
+            saliency_brush: MaskBrush::default(),
+            saliency_viewer: ImageViewer::default(),
+            saliency_mask: None,
+            saliency_weights: None,
+            paste_text: String::new(),
+            copy_format: ColorFormat::default(),
+            sheet_settings: SheetSettings::default(),
+            show_sheet_dialog: false,
+            terminal_format: TerminalFormat::default(),
+            lut_settings: LutSettings::default(),
+            sheet_preview: None,
+            style_text: None,
+            snippet_text: None,
         }
     }
 }
@@ -93,6 +269,7 @@ impl eframe::App for PaletteApp {
                 self.palette_list_panel(ui);
                 // self.backend_panel_contents(ui, frame, &mut cmd);
             });
+        self.hovered_color = None;
         egui::CentralPanel::default().show(ctx, |ui| {
             self.panel_width = (ui.available_width() - 20.0) / 2.0;
             ui.horizontal(|ui| {
@@ -110,7 +287,7 @@ impl eframe::App for PaletteApp {
 
                             ui.text_edit_singleline(&mut self.palette.palette_name);
 
-                            self.save_buttons(ui);
+                            self.save_buttons(ui, ctx);
                             self.reset_button(ui);
                         }
                     });
@@ -121,55 +298,6 @@ impl eframe::App for PaletteApp {
                     self.image_panel(ui, ctx);
                 });
             });
-
-            let img_start = egui::Vec2::new(870., 60.);
-            if self.color_picking {
-                ui.ctx()
-                    .send_viewport_cmd(ViewportCommand::Screenshot(UserData::default()));
-                ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
-                let mouse_pos = ctx.pointer_latest_pos().unwrap_or_default();
-                if mouse_pos != egui::pos2(0.0, 0.0) {
-                    // Draw the panel at the mouse position
-                    let panel_size = egui::Vec2::new(50., 50.);
-                    let panel_anchor = egui::Pos2::new(mouse_pos.x + 20., mouse_pos.y + 20.);
-                    let panel_rect = egui::Rect::from_min_size(panel_anchor, panel_size);
-                    // let text_position = panel_rect.center();
-
-                    let x = mouse_pos.x;
-                    let y = mouse_pos.y;
-
-                    if x > img_start[0] && y > img_start[1] {
-                        let pixel = self.get_pixel_at(ui, x, y);
-                        if let Some(p) = pixel {
-                            self.last_color_picked = Some(p);
-                            let r = p[0];
-                            let g = p[1];
-                            let b = p[2];
-                            // let str = format!("({x}, {y})\n ({r}, {g}, {b})");
-                            // Draw the text
-                            let stroke = egui::Stroke::new(3.0, egui::Color32::from_rgb(0, 0, 0)); // Red
-                            ui.painter().rect_stroke(
-                                panel_rect,
-                                5.0,
-                                stroke,
-                                egui::StrokeKind::Outside,
-                            );
-                            ui.painter().rect_filled(
-                                panel_rect,
-                                5.0,
-                                egui::Color32::from_rgb(r, g, b),
-                            );
-                            // ui.painter().text(
-                            //     text_position,
-                            //     egui::Align2::CENTER_CENTER,
-                            //     str,
-                            //     FontId::proportional(14.0),
-                            //     Color32::WHITE.gamma_multiply(1.),
-                            // );
-                        }
-                    }
-                }
-            }
         });
 
         preview_files_being_dropped(ctx);
@@ -192,15 +320,17 @@ impl PaletteApp {
             ui.set_min_width(400.);
             self.file_picker(ui);
             // Collect dropped files:
-            ctx.input(|i| {
-                if !i.raw.dropped_files.is_empty()
-                    && let Some(f_path) = &i.raw.dropped_files[0].path
-                {
-                    self.picked_path = Some(f_path.display().to_string());
-                    // self.
-                    self.source_file_state = SourceFileState::File;
-                }
+            let dropped = ctx.input(|i| {
+                i.raw
+                    .dropped_files
+                    .first()
+                    .and_then(|f| f.path.as_ref())
+                    .map(|p| p.display().to_string())
             });
+            if let Some(f_path) = dropped {
+                self.open_path(f_path);
+            }
+            self.paste_import(ui, ctx);
 
             if Self::base_button(ui, "New palette").clicked() {
                 self.palette = Palette::rand_palette();
@@ -209,55 +339,6 @@ impl PaletteApp {
         });
     }
 
-    fn get_pixel_at(&mut self, ui: &mut egui::Ui, x: f32, y: f32) -> Option<Rgb<u8>> {
-        let image = ui.ctx().input(|i| {
-            i.events
-                .iter()
-                .filter_map(|e| {
-                    if let egui::Event::Screenshot { image, .. } = e {
-                        Some(image.clone())
-                    } else {
-                        None
-                    }
-                })
-                .next_back()
-        });
-
-        match image {
-            Some(img) => {
-                let x_u = x as usize;
-                let y_u = y as usize;
-                // img.pixels()
-                // let [width, height] = img.();
-                let idx = img.width() * y_u + x_u;
-
-                if x_u < img.width() && y_u < img.height() {
-                    let pixel = img.pixels[idx].clone();
-                    // return (pixel[0], pixel[1], pixel[2], pixel[3]); // RGBA
-                    Some(Rgb([pixel[0], pixel[1], pixel[2]]))
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
-        //     Some(tex_handle) => {
-        //         // let data = tex_handle();
-        //         let [width, height] = tex_handle.size();
-
-        //         if x_u < width && y_u < height {
-        //             let pixel = tex_handle.pixel(x, y);
-        //             // return (pixel[0], pixel[1], pixel[2], pixel[3]); // RGBA
-        //             Some(image::Rgb([pixel[0], pixel[1], pixel[2]]))
-        //         } else {
-        //             None
-        //         }
-        //     }
-        //     None => None,
-        // }
-        // image::Rgb([0, 0, 0])
-    }
-
     fn similar_selector(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         let mut show_close = false;
         if let Some(sim) = &self.similar {
@@ -266,9 +347,7 @@ impl PaletteApp {
             let color = egui::Color32::from_rgb(c[0], c[1], c[2]);
             let hex = ColorUtil::rgb_to_hex(c);
             if Self::color_button(ui, color, &hex).clicked() {
-                let hex = ColorUtil::rgb_to_hex(c);
-                // println!("Copy {hex}");
-                ctx.copy_text(hex.to_owned());
+                ctx.copy_text(self.copy_format.format(c));
             }
             Self::color_info(ui, &c);
             egui::ScrollArea::horizontal()
@@ -279,10 +358,12 @@ impl PaletteApp {
                             let c = entry.0;
                             let color = egui::Color32::from_rgb(c[0], c[1], c[2]);
                             let hex = ColorUtil::rgb_to_hex(c);
-                            if Self::color_button(ui, color, &hex).clicked() {
-                                let hex = ColorUtil::rgb_to_hex(c);
-                                // println!("Copy {hex}");
-                                ctx.copy_text(hex.to_owned());
+                            let response = Self::color_button(ui, color, &hex);
+                            if response.hovered() {
+                                self.hovered_color = Some(c);
+                            }
+                            if response.clicked() {
+                                ctx.copy_text(self.copy_format.format(c));
                             }
                             Self::color_info(ui, &c);
                             if Self::base_button(ui, "Replace").clicked() {
@@ -307,6 +388,7 @@ impl PaletteApp {
     }
 
     fn palette_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        self.update_coverage();
         // Create a grid and add items to it
         ui.horizontal(|ui| {
             ui.set_min_height(500.);
@@ -327,13 +409,29 @@ impl PaletteApp {
         });
     }
 
+    fn update_coverage(&mut self) {
+        // Shares only mean something for palettes extracted from an image
+        if self.palette.current_path.is_none() {
+            self.coverage = None;
+            return;
+        }
+        let stale = match &self.coverage {
+            Some((colors, _)) => *colors != self.palette.top_rgb,
+            None => true,
+        };
+        if stale {
+            let shares = Coverage::palette_shares(&self.palette.top_rgb, &self.palette.all_entries);
+            self.coverage = Some((self.palette.top_rgb.clone(), shares));
+        }
+    }
+
     fn color_options_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         if let Some(detail) = &self.show_details {
             ui.horizontal(|ui| {
                 ui.vertical(|ui| {
                     ui.label("Color");
                     if Self::color_button(ui, detail.egui_color, &detail.hex).clicked() {
-                        ctx.copy_text(detail.hex.to_owned());
+                        ctx.copy_text(self.copy_format.format(detail.color));
                     }
                     Self::color_info(ui, &detail.color);
                 });
@@ -350,7 +448,7 @@ impl PaletteApp {
                     if Self::color_button(ui, detail.compliment_egui, &detail.complement_hex)
                         .clicked()
                     {
-                        ctx.copy_text(detail.complement_hex.to_owned());
+                        ctx.copy_text(self.copy_format.format(detail.complement));
                     }
                     Self::color_info(ui, &detail.complement);
                     // self.add_color_btn(ui, detail.complement);
@@ -364,7 +462,7 @@ impl PaletteApp {
                     )
                     .clicked()
                     {
-                        ctx.copy_text(detail.split_complement_hex.0.to_owned());
+                        ctx.copy_text(self.copy_format.format(detail.split_complement.0));
                     }
                     Self::color_info(ui, &detail.split_complement.0);
                     // self.add_color_btn(ui, detail.complement);
@@ -379,7 +477,7 @@ impl PaletteApp {
                     )
                     .clicked()
                     {
-                        ctx.copy_text(detail.split_complement_hex.1.to_owned());
+                        ctx.copy_text(self.copy_format.format(detail.split_complement.1));
                     }
                     Self::color_info(ui, &detail.split_complement.1);
                     // self.add_color_btn(ui, detail.split_complem`nt.1);
@@ -418,6 +516,16 @@ impl PaletteApp {
             if ui.button(p.palette_name.clone()).clicked() {
                 self.palette = p.clone();
                 self.texture_id = None;
+                self.source_image = None;
+                self.source_color_space = None;
+                self.segmentation = None;
+                self.segment_overlay = None;
+                self.saliency_mask = None;
+                self.saliency_weights = None;
+                self.pixelated = None;
+                self.highlight = None;
+                self.heatmap = None;
+                self.adjusted = None;
                 if p.current_path.is_some() {
                     self.source_file_state = SourceFileState::File;
                     self.picked_path = p.current_path.clone();
@@ -440,7 +548,7 @@ impl PaletteApp {
 
         if Self::base_button(ui, "Copy").clicked() {
             self.new_color.update_from_egui_color(false);
-            ctx.copy_text(self.new_color.hex.to_owned());
+            ctx.copy_text(self.copy_format.format(self.new_color.color));
         }
     }
 
@@ -453,8 +561,25 @@ impl PaletteApp {
         let hex = &self.palette.top_hex[i];
         let color = egui::Color32::from_rgb(c[0], c[1], c[2]);
 
-        if Self::color_button(ui, color, hex).clicked() {
-            ctx.copy_text(hex.to_owned());
+        let share = self.coverage.as_ref().and_then(|(_, s)| s.get(i).copied());
+        let response = ui
+            .vertical(|ui| {
+                let response = Self::color_button(ui, color, hex);
+                if let Some(share) = share {
+                    ui.add(
+                        egui::ProgressBar::new(share)
+                            .desired_width(PALETTE_BUTTON_SIZE.x)
+                            .text(format!("{:.1}%", share * 100.)),
+                    );
+                }
+                response
+            })
+            .inner;
+        if response.hovered() {
+            self.hovered_color = Some(c);
+        }
+        if response.clicked() {
+            ctx.copy_text(self.copy_format.format(c));
         }
         ui.vertical(|ui| {
             ui.set_min_width(90.);
@@ -487,18 +612,50 @@ impl PaletteApp {
             if ui.button("+").clicked() {
                 self.palette.increment_palette_size();
             }
+            egui::ComboBox::from_label("Copy as")
+                .selected_text(self.copy_format.label())
+                .show_ui(ui, |ui| {
+                    for format in ColorFormat::ALL {
+                        ui.selectable_value(&mut self.copy_format, format, format.label());
+                    }
+                });
         });
     }
 
-    fn save_buttons(&mut self, ui: &mut egui::Ui) {
-        if ui.button("Save as PNG").clicked() {
-            println!("Save clicked");
-            self.palette.save_palette_img(self.palette_name.clone())
+    fn save_buttons(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        if ui.button("Swatch sheet…").clicked() {
+            self.show_sheet_dialog = true;
+        }
+        self.swatch_sheet_dialog(ctx);
+        if ui.button("Style guide (HTML)…").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("HTML", &["html"])
+                .set_file_name(format!("{}.html", self.palette.palette_name))
+                .save_file()
+            && let Err(e) = fs::write(path, StyleGuide::render_html(&self.palette))
+        {
+            eprintln!("Error exporting {}", e);
         }
         if ui.button("Save as Text").clicked() {
             println!("Save clicked");
             self.palette.save_palette_text(self.palette_name.clone())
         }
+        if ui.button("Export palette…").clicked() {
+            let mut dialog =
+                rfd::FileDialog::new().set_file_name(format!("{}.gpl", self.palette_name));
+            for format in PALETTE_FORMATS {
+                dialog = dialog.add_filter(format.name(), format.extensions());
+            }
+            if let Some(path) = dialog.save_file()
+                && let Err(e) = self.palette.save_file(&path.display().to_string())
+            {
+                eprintln!("Error exporting palette {}", e);
+            }
+        }
+        self.style_export_panel(ui, ctx);
+        self.code_snippet_panel(ui, ctx);
+        self.terminal_theme_panel(ui, ctx);
+        self.shader_lut_panel(ui);
         if ui.button("Save to List").clicked() {
             println!("Save to list");
             let idx = self
@@ -536,6 +693,407 @@ impl PaletteApp {
         }
     }
 
+    /// Layout options and preview for the PNG, SVG and PDF swatch sheets.
+    fn swatch_sheet_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sheet_dialog;
+        let count = self.palette.top_rgb.len();
+        egui::Window::new("Swatch sheet")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for preset in SheetPreset::ALL {
+                        if ui.button(preset.label()).clicked() {
+                            self.sheet_settings = SheetSettings::preset(preset, count);
+                        }
+                    }
+                });
+                let settings = &mut self.sheet_settings;
+                egui::Grid::new("sheet_settings").show(ui, |ui| {
+                    ui.label("Swatch size");
+                    ui.add(egui::DragValue::new(&mut settings.swatch_size).range(1..=512));
+                    ui.end_row();
+                    ui.label("Columns");
+                    ui.add(egui::DragValue::new(&mut settings.columns).range(1..=count.max(1)));
+                    ui.end_row();
+                    ui.label("Margin");
+                    ui.add(egui::DragValue::new(&mut settings.margin).range(0..=256));
+                    ui.end_row();
+                    ui.label("Gap");
+                    ui.add(egui::DragValue::new(&mut settings.gap).range(0..=256));
+                    ui.end_row();
+                    ui.label("Background");
+                    let mut background = ColorUtil::rgb_to_egui(&settings.background);
+                    if ui.color_edit_button_srgba(&mut background).changed() {
+                        settings.background = Rgb([background.r(), background.g(), background.b()]);
+                    }
+                    ui.end_row();
+                    ui.label("Labels");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut settings.label_hex, "Hex");
+                        ui.checkbox(&mut settings.label_rgb, "RGB");
+                        ui.checkbox(&mut settings.label_cmyk, "CMYK");
+                        ui.checkbox(&mut settings.label_name, "Name");
+                    });
+                    ui.end_row();
+                    ui.label("Font size");
+                    ui.add(egui::DragValue::new(&mut settings.font_size).range(6.0..=48.0));
+                    ui.end_row();
+                });
+
+                let stale = match &self.sheet_preview {
+                    Some((s, colors, _)) => {
+                        *s != self.sheet_settings || *colors != self.palette.top_rgb
+                    }
+                    None => true,
+                };
+                if stale {
+                    let img = SwatchSheet::render_png(&self.palette, &self.sheet_settings);
+                    let preview = ColorImage::from_rgba_unmultiplied(
+                        [img.width() as usize, img.height() as usize],
+                        img.as_raw(),
+                    );
+                    let texture =
+                        ctx.load_texture("swatch_sheet", preview, egui::TextureOptions::NEAREST);
+                    self.sheet_preview = Some((
+                        self.sheet_settings.clone(),
+                        self.palette.top_rgb.clone(),
+                        texture,
+                    ));
+                }
+                if let Some((_, _, texture)) = &self.sheet_preview {
+                    let size = texture.size_vec2();
+                    ui.label(format!("{} × {} px", size.x, size.y));
+                    // Tiny sheets like the 1px strip are scaled up to stay visible
+                    let scale = (ui.available_width() / size.x)
+                        .min(200. / size.y)
+                        .min(if size.x < 64. { 16. } else { 1. });
+                    ui.image((texture.id(), size * scale));
+                }
+
+                ui.horizontal(|ui| {
+                    let name = &self.palette.palette_name;
+                    if ui.button("Export PNG…").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("PNG", &["png"])
+                            .set_file_name(format!("{name}.png"))
+                            .save_file()
+                        && let Err(e) =
+                            SwatchSheet::render_png(&self.palette, &self.sheet_settings).save(path)
+                    {
+                        eprintln!("Error exporting {}", e);
+                    }
+                    if ui.button("Export SVG…").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("SVG", &["svg"])
+                            .set_file_name(format!("{name}.svg"))
+                            .save_file()
+                        && let Err(e) = fs::write(
+                            path,
+                            SwatchSheet::render_svg(&self.palette, &self.sheet_settings),
+                        )
+                    {
+                        eprintln!("Error exporting {}", e);
+                    }
+                    if ui.button("Export PDF…").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("PDF", &["pdf"])
+                            .set_file_name(format!("{name}.pdf"))
+                            .save_file()
+                        && let Err(e) = fs::write(
+                            path,
+                            SwatchSheet::render_pdf(&self.palette, &self.sheet_settings),
+                        )
+                    {
+                        eprintln!("Error exporting {}", e);
+                    }
+                });
+            });
+        self.show_sheet_dialog = open;
+    }
+
+    fn export_inputs(&self) -> ExportInputs {
+        (
+            self.palette.top_rgb.clone(),
+            self.palette.color_names.clone(),
+            self.palette.roles.clone(),
+            self.palette.palette_name.clone(),
+        )
+    }
+
+    /// CSS, SCSS, Tailwind or DTCG text for the palette, with a preview.
+    fn style_export_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        egui::CollapsingHeader::new("Code export").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for format in StyleFormat::ALL {
+                    ui.selectable_value(&mut self.style_format, format, format.label());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Names");
+                for naming in TokenNaming::ALL {
+                    ui.selectable_value(&mut self.token_settings.naming, naming, naming.label());
+                }
+                ui.label("Prefix");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.token_settings.prefix).desired_width(80.),
+                );
+            });
+            ui.horizontal(|ui| {
+                for gamut in Gamut::ALL {
+                    ui.selectable_value(&mut self.token_settings.gamut, gamut, gamut.label());
+                }
+            });
+            if self.token_settings.naming == TokenNaming::Role {
+                self.role_editor(ui);
+            }
+
+            let inputs = self.export_inputs();
+            let stale = match &self.style_text {
+                Some((format, settings, used, _)) => {
+                    *format != self.style_format
+                        || *settings != self.token_settings
+                        || *used != inputs
+                }
+                None => true,
+            };
+            if stale {
+                let text = self.palette.to_style(
+                    self.style_format,
+                    &self.token_settings,
+                    &self.palette.palette_name,
+                );
+                self.style_text =
+                    Some((self.style_format, self.token_settings.clone(), inputs, text));
+            }
+            let Some((_, _, _, text)) = &self.style_text else {
+                return;
+            };
+            ui.horizontal(|ui| {
+                if ui.button("Copy").clicked() {
+                    ctx.copy_text(text.clone());
+                }
+                let extension = self.style_format.extension();
+                if ui.button("Export…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(self.style_format.label(), &[extension])
+                        .set_file_name(format!("{}.{extension}", self.palette.palette_name))
+                        .save_file()
+                    && let Err(e) = fs::write(path, text)
+                {
+                    eprintln!("Error exporting {}", e);
+                }
+            });
+            egui::ScrollArea::vertical()
+                .max_height(200.)
+                .show(ui, |ui| {
+                    ui.add(egui::TextEdit::multiline(&mut text.as_str()).code_editor());
+                });
+        });
+    }
+
+    /// Palette rendered through a built-in or user code template.
+    fn code_snippet_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        egui::CollapsingHeader::new("Code snippets").show(ui, |ui| {
+            if self.code_templates.is_empty() {
+                self.code_templates = CodeTemplate::all();
+            }
+            let selected = self
+                .code_templates
+                .iter()
+                .position(|t| t.name == self.code_template)
+                .unwrap_or(0);
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Template")
+                    .selected_text(&self.code_templates[selected].name)
+                    .show_ui(ui, |ui| {
+                        for template in &self.code_templates {
+                            ui.selectable_value(
+                                &mut self.code_template,
+                                template.name.clone(),
+                                &template.name,
+                            );
+                        }
+                    });
+                if ui
+                    .button("Reload")
+                    .on_hover_text(format!("Add your own as {TEMPLATE_DIR}/<name>.<ext>.tmpl"))
+                    .clicked()
+                {
+                    self.code_templates = CodeTemplate::all();
+                }
+            });
+
+            let Some(template) = self.code_templates.get(selected) else {
+                return;
+            };
+            let inputs = self.export_inputs();
+            let stale = match &self.snippet_text {
+                Some((used_template, used, _)) => used_template != template || *used != inputs,
+                None => true,
+            };
+            if stale {
+                let text = template.render(&self.palette, &self.palette.palette_name);
+                self.snippet_text = Some((template.clone(), inputs, text));
+            }
+            let Some((_, _, text)) = &self.snippet_text else {
+                return;
+            };
+            ui.horizontal(|ui| {
+                if ui.button("Copy").clicked() {
+                    ctx.copy_text(text.clone());
+                }
+                let extension = &template.extension;
+                if ui.button("Export…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(&template.name, &[extension])
+                        .set_file_name(format!("{}.{extension}", self.palette.palette_name))
+                        .save_file()
+                    && let Err(e) = fs::write(path, text)
+                {
+                    eprintln!("Error exporting {}", e);
+                }
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("code_snippet_preview")
+                .max_height(200.)
+                .show(ui, |ui| {
+                    ui.add(egui::TextEdit::multiline(&mut text.as_str()).code_editor());
+                });
+        });
+    }
+
+    /// ANSI slot assignment and terminal or editor theme export.
+    fn terminal_theme_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        egui::CollapsingHeader::new("Terminal theme").show(ui, |ui| {
+            let colors = self.palette.top_rgb.clone();
+            let theme = self
+                .palette
+                .terminal_theme
+                .get_or_insert_with(|| TerminalTheme::suggest(&colors));
+            if ui.button("Suggest").clicked() {
+                *theme = TerminalTheme::suggest(&colors);
+            }
+            egui::Grid::new("terminal_slots")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (i, slot) in theme.slots.iter_mut().enumerate() {
+                        egui::ComboBox::from_id_salt(("terminal_slot", i))
+                            .selected_text(
+                                egui::RichText::new("■■").color(ColorUtil::rgb_to_egui(slot)),
+                            )
+                            .width(40.)
+                            .show_ui(ui, |ui| {
+                                for c in &colors {
+                                    let text = egui::RichText::new(format!(
+                                        "■■ {}",
+                                        ColorUtil::rgb_to_hex(*c)
+                                    ))
+                                    .color(ColorUtil::rgb_to_egui(c));
+                                    ui.selectable_value(slot, *c, text);
+                                }
+                            });
+                        ui.label(TerminalTheme::slot_name(i));
+                        if i % 2 == 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                for format in TerminalFormat::ALL {
+                    ui.selectable_value(&mut self.terminal_format, format, format.label());
+                }
+            });
+            let text = theme.export(self.terminal_format, &self.palette.palette_name);
+            ui.horizontal(|ui| {
+                if ui.button("Copy").clicked() {
+                    ctx.copy_text(text.clone());
+                }
+                let extension = self.terminal_format.extension();
+                if ui.button("Export…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(self.terminal_format.label(), &[extension])
+                        .set_file_name(format!("{}.{extension}", self.palette.palette_name))
+                        .save_file()
+                    && let Err(e) = fs::write(path, &text)
+                {
+                    eprintln!("Error exporting {}", e);
+                }
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("terminal_theme_preview")
+                .max_height(200.)
+                .show(ui, |ui| {
+                    ui.add(egui::TextEdit::multiline(&mut text.as_str()).code_editor());
+                });
+        });
+    }
+
+    /// Lookup texture and `.cube` exports for palette restriction shaders.
+    fn shader_lut_panel(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Shader LUT").show(ui, |ui| {
+            let name = &self.palette.palette_name;
+            let settings = &mut self.lut_settings;
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut settings.orientation, TextureOrientation::Row, "N×1");
+                ui.selectable_value(&mut settings.orientation, TextureOrientation::Column, "1×N");
+                if ui.button("Export texture…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("PNG", &["png"])
+                        .set_file_name(format!("{name}_lookup.png"))
+                        .save_file()
+                    && let Err(e) =
+                        Lut::lookup_texture(&self.palette.top_rgb, settings.orientation).save(path)
+                {
+                    eprintln!("Error exporting {}", e);
+                }
+            });
+            ui.horizontal(|ui| {
+                for size in CUBE_SIZES {
+                    ui.selectable_value(&mut settings.cube_size, size, format!("{size}³"));
+                }
+                for mapping in LutMapping::ALL {
+                    ui.selectable_value(&mut settings.mapping, mapping, mapping.label());
+                }
+            });
+            if ui.button("Export .cube…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("3D LUT", &["cube"])
+                    .set_file_name(format!("{name}.cube"))
+                    .save_file()
+                && let Err(e) = fs::write(path, Lut::cube(&self.palette.top_rgb, settings, name))
+            {
+                eprintln!("Error exporting {}", e);
+            }
+        });
+    }
+
+    fn role_editor(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("color_roles").show(ui, |ui| {
+            for c in self.palette.top_rgb.clone() {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(16., 16.), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2., ColorUtil::rgb_to_egui(&c));
+                ui.label(ColorUtil::rgb_to_hex(c));
+                let current = self.palette.role(&c).map(str::to_string);
+                let mut selected = current.clone();
+                egui::ComboBox::from_id_salt(("color_role", c.0))
+                    .selected_text(current.as_deref().unwrap_or("—"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "—");
+                        for role in ROLES {
+                            ui.selectable_value(&mut selected, Some(role.to_string()), *role);
+                        }
+                    });
+                if selected != current {
+                    self.palette.set_role(c, selected);
+                }
+                ui.end_row();
+            }
+        });
+    }
+
     fn save_palette_list(&self) -> Result<(), Box<dyn Error>> {
         println!("Try save palette list");
         let path = "data.json";
@@ -545,7 +1103,7 @@ impl PaletteApp {
         Ok(())
     }
 
-    fn load_palette_list() -> Result<Vec<Palette>, Box<dyn Error>> {
+    pub(crate) fn load_palette_list() -> Result<Vec<Palette>, Box<dyn Error>> {
         println!("Try load palette list");
         let path = "data.json";
         let json = fs::read_to_string(path)?;
@@ -566,6 +1124,16 @@ impl PaletteApp {
         self.app_state = AppState::Nopalette;
         self.source_file_state = SourceFileState::NoFile;
         self.texture_id = None;
+        self.source_image = None;
+        self.source_color_space = None;
+        self.segmentation = None;
+        self.segment_overlay = None;
+        self.saliency_mask = None;
+        self.saliency_weights = None;
+        self.pixelated = None;
+        self.highlight = None;
+        self.heatmap = None;
+        self.adjusted = None;
         self.similar = None
     }
 
@@ -573,17 +1141,75 @@ impl PaletteApp {
         if self.picked_path.is_none() {
             ui.label("Drag and drop a file to create a palette");
 
-            if ui.button("Open file…").clicked()
-                && let Some(path) = rfd::FileDialog::new()
+            if ui.button("Open file…").clicked() {
+                let palette_extensions: Vec<&str> = PALETTE_FORMATS
+                    .iter()
+                    .flat_map(|f| f.extensions())
+                    .copied()
+                    .collect();
+                let mut dialog = rfd::FileDialog::new()
                     .add_filter("Images", &["png", "jpg", "jpeg", "webp"])
-                    .pick_file()
-            {
-                self.picked_path = Some(path.display().to_string());
-                self.source_file_state = SourceFileState::File;
+                    .add_filter("Palettes", &palette_extensions)
+                    .add_filter("Text with colors", TEXT_EXTENSIONS);
+                for format in PALETTE_FORMATS {
+                    dialog = dialog.add_filter(format.name(), format.extensions());
+                }
+                if let Some(path) = dialog.pick_file() {
+                    self.open_path(path.display().to_string());
+                }
+            }
+        }
+    }
+
+    /// Text box for colors, plus ctrl+V anywhere else in the view.
+    fn paste_import(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let edit = ui.add(
+            egui::TextEdit::multiline(&mut self.paste_text)
+                .hint_text("Paste hex, rgb() or hsl() colors, CSS, code…")
+                .desired_rows(3),
+        );
+        let pasted = if edit.has_focus() {
+            None
+        } else {
+            ctx.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            })
+        };
+        let text = match pasted {
+            Some(text) => text,
+            None if ui.button("Import colors").clicked() => self.paste_text.clone(),
+            None => return,
+        };
+        match Palette::from_text(&text) {
+            Ok(mut palette) => {
+                palette.palette_name = "Pasted colors".to_string();
+                self.palette = palette;
+                self.paste_text.clear();
+                self.app_state = AppState::PaletteGenerated;
             }
+            Err(e) => println!("Error importing colors {}", e),
         }
     }
 
+    /// Imports palette files directly, anything else is treated as a source image.
+    fn open_path(&mut self, path: String) {
+        if Palette::is_palette_file(&path) {
+            match Palette::load_file(&path) {
+                Ok(palette) => {
+                    self.palette = palette;
+                    self.app_state = AppState::PaletteGenerated;
+                }
+                Err(e) => println!("Error importing palette {}", e),
+            }
+            return;
+        }
+        self.picked_path = Some(path);
+        self.source_file_state = SourceFileState::File;
+    }
+
     fn image_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.vertical_centered(|ui| {
             ui.set_min_width(self.panel_width);
@@ -594,7 +1220,20 @@ impl PaletteApp {
                     ui.image(egui::include_image!("../assets/palette.svg"));
                 }
                 SourceFileState::File => {
-                    self.color_selectable_img(ui, ctx);
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.image_view, ImageView::Source, "Source");
+                        ui.selectable_value(&mut self.image_view, ImageView::Adjust, "Adjust");
+                        ui.selectable_value(&mut self.image_view, ImageView::Pixelate, "Pixelate");
+                        ui.selectable_value(&mut self.image_view, ImageView::Segments, "Segments");
+                        ui.selectable_value(&mut self.image_view, ImageView::Saliency, "Saliency");
+                    });
+                    match self.image_view {
+                        ImageView::Source => self.color_selectable_img(ui, ctx),
+                        ImageView::Adjust => self.adjust_panel(ui, ctx),
+                        ImageView::Pixelate => self.pixelate_panel(ui, ctx),
+                        ImageView::Segments => self.segments_panel(ui, ctx),
+                        ImageView::Saliency => self.saliency_panel(ui, ctx),
+                    }
                 }
             }
         });
@@ -610,19 +1249,57 @@ impl PaletteApp {
                     .clicked()
                 {
                     self.color_picking = !self.color_picking;
+                    self.path_tool = false;
                 }
-            });
-            if let Some(texture_id) = &self.texture_id {
-                let desired_size = egui::vec2(300.0, 500.0);
-
                 if ui
-                    .add(
-                        egui::Image::new(texture_id)
-                            .fit_to_exact_size(desired_size)
-                            .sense(egui::Sense::click()),
-                    )
+                    .selectable_label(self.path_tool, "Path")
+                    .on_hover_text("Drag across the image to sample colors along a path, hold shift for a straight line")
                     .clicked()
                 {
+                    self.path_tool = !self.path_tool;
+                    self.color_picking = false;
+                }
+                if self.color_picking || self.path_tool {
+                    for size in [SampleSize::Point, SampleSize::Three, SampleSize::Five] {
+                        ui.selectable_value(&mut self.sample_size, size, size.label());
+                    }
+                }
+            });
+            if self.path_tool {
+                self.path_controls(ui);
+            }
+            self.highlight_controls(ui, ctx);
+            if let Some(texture_id) = &self.texture_id {
+                self.image_viewer.controls(ui);
+                if let Some(space) = &self.source_color_space {
+                    ui.label(format!("Color space: {}", space.label()));
+                }
+                let viewport_size = egui::vec2(ui.available_width(), 500.0);
+                let viewer =
+                    self.image_viewer
+                        .show(
+                        ui,
+                        texture_id,
+                        viewport_size,
+                        !self.color_picking && !self.path_tool,
+                    );
+                if self.show_heatmap
+                    && let Some((_, _, heat_texture)) = &self.heatmap
+                {
+                    viewer.paint_overlay(ui, heat_texture);
+                }
+                if self.hovered_color.is_some()
+                    && let Some((_, mask_texture)) = &self.highlight
+                {
+                    viewer.paint_overlay(ui, mask_texture);
+                }
+                if self.color_picking {
+                    self.eyedropper(ctx, &viewer);
+                }
+                if self.path_tool {
+                    self.path_tool_input(ui, &viewer);
+                }
+                if viewer.response.clicked() {
                     if self.color_picking
                         && let Some(c) = self.last_color_picked
                     {
@@ -634,11 +1311,16 @@ impl PaletteApp {
                 ui.label("Loading image...");
 
                 if let Some(picked_path) = &self.picked_path {
-                    if let Ok(img) = load_image(picked_path) {
-                        let color_image = convert_img_for_display(img);
+                    if let Ok((img, space)) = load_image(picked_path) {
+                        let color_image = convert_img_for_display(img.clone());
+                        self.source_image = Some(img);
+                        self.source_color_space = Some(space);
                         // self.loaded_img = Some(color_image);
-                        self.texture_id =
-                            Some(ctx.load_texture("my_image", color_image, Default::default()));
+                        self.texture_id = Some(ctx.load_texture(
+                            "my_image",
+                            color_image,
+                            viewer_texture_options(),
+                        ));
                     }
                     ui.horizontal(|ui| {
                         ui.label("Picked file:");
@@ -653,6 +1335,615 @@ impl PaletteApp {
         });
     }
 
+    fn eyedropper(&mut self, ctx: &egui::Context, viewer: &ImageViewerResponse) {
+        self.last_color_picked = None;
+        let (Some(img), Some(pos)) = (&self.source_image, viewer.response.hover_pos()) else {
+            return;
+        };
+        let Some((x, y)) = viewer.image_pixel(pos, img.dimensions()) else {
+            return;
+        };
+        ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+        self.last_color_picked = Eyedropper::sample(img, x, y, self.sample_size);
+        Self::draw_loupe(
+            ctx,
+            img,
+            pos,
+            (x, y),
+            self.sample_size,
+            self.last_color_picked,
+        );
+    }
+
+    fn draw_loupe(
+        ctx: &egui::Context,
+        img: &DynamicImage,
+        pointer: egui::Pos2,
+        pixel: (u32, u32),
+        sample_size: SampleSize,
+        picked: Option<Rgb<u8>>,
+    ) {
+        let half = LOUPE_PIXELS / 2;
+        let cells = Eyedropper::neighborhood(img, pixel.0, pixel.1, half);
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Tooltip,
+            egui::Id::new("eyedropper_loupe"),
+        ));
+        let loupe_size = LOUPE_CELL * LOUPE_PIXELS as f32;
+        let origin = pointer + egui::vec2(20., 20.);
+        let loupe_rect = egui::Rect::from_min_size(origin, egui::Vec2::splat(loupe_size));
+        painter.rect_filled(loupe_rect, 0.0, egui::Color32::from_gray(64));
+        let grid_stroke = egui::Stroke::new(1.0, egui::Color32::from_black_alpha(80));
+        for (row, line) in cells.iter().enumerate() {
+            for (col, cell) in line.iter().enumerate() {
+                let min = origin + egui::vec2(col as f32 * LOUPE_CELL, row as f32 * LOUPE_CELL);
+                let rect = egui::Rect::from_min_size(min, egui::Vec2::splat(LOUPE_CELL));
+                if let Some(c) = cell {
+                    painter.rect_filled(rect, 0.0, *c);
+                }
+                painter.rect_stroke(rect, 0.0, grid_stroke, egui::StrokeKind::Inside);
+            }
+        }
+        // Outline the pixels that are averaged into the sample
+        let r = sample_size.radius() as f32;
+        let sample_rect = egui::Rect::from_min_size(
+            origin + egui::Vec2::splat((half as f32 - r) * LOUPE_CELL),
+            egui::Vec2::splat((2. * r + 1.) * LOUPE_CELL),
+        );
+        painter.rect_stroke(
+            sample_rect,
+            0.0,
+            egui::Stroke::new(2.0, egui::Color32::WHITE),
+            egui::StrokeKind::Outside,
+        );
+        painter.rect_stroke(
+            loupe_rect,
+            5.0,
+            egui::Stroke::new(3.0, egui::Color32::from_rgb(0, 0, 0)),
+            egui::StrokeKind::Outside,
+        );
+
+        if let Some(c) = picked {
+            let swatch_rect = egui::Rect::from_min_size(
+                loupe_rect.left_bottom() + egui::vec2(0., 6.),
+                egui::vec2(loupe_size, 24.),
+            );
+            painter.rect_filled(swatch_rect, 5.0, ColorUtil::rgb_to_egui(&c));
+            painter.text(
+                swatch_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                format!("{} ({}, {})", ColorUtil::rgb_to_hex(c), pixel.0, pixel.1),
+                egui::FontId::proportional(12.0),
+                ColorUtil::contrast_text(&c),
+            );
+        }
+    }
+
+    fn path_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Samples");
+            let changed = ui
+                .add(egui::DragValue::new(&mut self.path_sampler.sample_count).range(2..=64))
+                .changed();
+            if changed && let Some(img) = &self.source_image {
+                self.path_sampler.sample(img, self.sample_size);
+            }
+            if ui.button("Clear path").clicked() {
+                self.path_sampler.clear();
+            }
+            if !self.path_sampler.colors.is_empty() && ui.button("Add to palette").clicked() {
+                for c in self.path_sampler.colors.clone() {
+                    self.palette.add_new_color(c);
+                }
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            for c in &self.path_sampler.colors {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(20., 20.), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2.0, ColorUtil::rgb_to_egui(c));
+            }
+        });
+    }
+
+    fn path_tool_input(&mut self, ui: &egui::Ui, viewer: &ImageViewerResponse) {
+        let Some(img) = &self.source_image else {
+            return;
+        };
+        let img_size = img.dimensions();
+        let response = &viewer.response;
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            self.path_sampler.clear();
+            self.path_sampler
+                .points
+                .push(viewer.image_pos(pos, img_size));
+        } else if response.dragged_by(egui::PointerButton::Primary)
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let p = viewer.image_pos(pos, img_size);
+            let straight = ui.input(|i| i.modifiers.shift);
+            let points = &mut self.path_sampler.points;
+            if straight {
+                points.truncate(1);
+                points.push(p);
+            } else if let Some(last) = points.last() {
+                // Skip points closer than a screen point to keep the path light
+                let min_step = img_size.0 as f32 / viewer.image_rect.width();
+                if f32::hypot(p.0 - last.0, p.1 - last.1) >= min_step {
+                    points.push(p);
+                }
+            }
+        } else if response.drag_stopped() {
+            self.path_sampler.sample(img, self.sample_size);
+        }
+
+        let stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+        let painter = viewer.painter(ui);
+        let line: Vec<egui::Pos2> = self
+            .path_sampler
+            .points
+            .iter()
+            .map(|p| viewer.screen_pos(p.0, p.1, img_size))
+            .collect();
+        painter.add(egui::Shape::line(line, stroke));
+        for (p, c) in self
+            .path_sampler
+            .sample_positions()
+            .iter()
+            .zip(self.path_sampler.colors.iter())
+        {
+            let center = viewer.screen_pos(p.0, p.1, img_size);
+            painter.circle(center, 5.0, ColorUtil::rgb_to_egui(c), stroke);
+        }
+    }
+
+    fn highlight_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.label("Highlight ΔE");
+            ui.add(egui::Slider::new(&mut self.highlight_delta_e, 1.0..=50.0));
+            ui.checkbox(&mut self.show_heatmap, "Error heatmap");
+        });
+        self.heatmap_controls(ui, ctx);
+        let (Some(c), Some(img)) = (self.hovered_color, &self.source_image) else {
+            ui.label(" ");
+            return;
+        };
+        let stale = match &self.highlight {
+            Some((h, _)) => h.color != c || h.delta_e != self.highlight_delta_e,
+            None => true,
+        };
+        if stale {
+            let highlight = Highlight::new(&img.to_rgba8(), c, self.highlight_delta_e);
+            let texture = ctx.load_texture(
+                "highlight_mask",
+                highlight.mask.clone(),
+                egui::TextureOptions::NEAREST,
+            );
+            self.highlight = Some((highlight, texture));
+        }
+        if let Some((h, _)) = &self.highlight {
+            let hex = ColorUtil::rgb_to_hex(h.color);
+            let pct = h.coverage * 100.;
+            ui.label(format!("{hex} covers {pct:.1}% of the image"));
+        }
+    }
+
+    fn heatmap_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let Some(img) = &self.source_image else {
+            return;
+        };
+        if !self.show_heatmap {
+            return;
+        }
+        let stale = match &self.heatmap {
+            Some((colors, _, _)) => *colors != self.palette.top_rgb,
+            None => true,
+        };
+        if stale {
+            let (heat_image, mean) =
+                Coverage::error_heatmap(&img.to_rgba8(), &self.palette.top_rgb);
+            let texture =
+                ctx.load_texture("error_heatmap", heat_image, egui::TextureOptions::NEAREST);
+            self.heatmap = Some((self.palette.top_rgb.clone(), mean, texture));
+        }
+        if let Some((_, mean, _)) = &self.heatmap {
+            ui.label(format!("Mean ΔE to nearest palette color: {mean:.1}"));
+        }
+    }
+
+    fn adjust_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let Some(img) = &self.source_image else {
+            ui.label("Open the Source view to load the image");
+            return;
+        };
+        let (width, height) = img.dimensions();
+        ui.horizontal(|ui| {
+            ui.menu_button("Add adjustment", |ui| {
+                for adjustment in Adjustment::all_defaults(width, height) {
+                    if ui.button(adjustment.name()).clicked() {
+                        self.adjustments.adjustments.push((true, adjustment));
+                    }
+                }
+            });
+            if ui.button("Clear").clicked() {
+                self.adjustments.adjustments.clear();
+            }
+        });
+
+        let mut remove = None;
+        let mut move_up = None;
+        let stack_len = self.adjustments.adjustments.len();
+        for (i, (enabled, adjustment)) in self.adjustments.adjustments.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(enabled, adjustment.name());
+                Self::adjustment_params(ui, adjustment, width, height);
+                if i > 0 && ui.small_button("⏶").clicked() {
+                    move_up = Some(i);
+                }
+                if i + 1 < stack_len && ui.small_button("⏷").clicked() {
+                    move_up = Some(i + 1);
+                }
+                if ui.small_button("🗑").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = move_up {
+            self.adjustments.adjustments.swap(i - 1, i);
+        }
+        if let Some(i) = remove {
+            self.adjustments.adjustments.remove(i);
+        }
+
+        // Wait for drags to finish so slow adjustments don't run every frame
+        let stale = match &self.adjusted {
+            Some((stack, _, _)) => *stack != self.adjustments,
+            None => true,
+        };
+        if stale && !ui.input(|i| i.pointer.any_down()) {
+            let adjusted = self.adjustments.apply(img);
+            let texture = ctx.load_texture(
+                "adjusted_image",
+                convert_img_for_display(adjusted.clone()),
+                viewer_texture_options(),
+            );
+            self.adjusted = Some((self.adjustments.clone(), adjusted, texture));
+        }
+
+        if let Some((_, adjusted, texture)) = &self.adjusted {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} x {}", adjusted.width(), adjusted.height()));
+                if let Some(picked_path) = &self.picked_path
+                    && ui.button("Extract palette from adjusted image").clicked()
+                {
+                    self.palette.update_from_image(picked_path, adjusted);
+                    self.app_state = AppState::PaletteGenerated;
+                }
+            });
+            self.adjust_viewer.controls(ui);
+            let viewport_size = egui::vec2(ui.available_width(), 500.0);
+            self.adjust_viewer.show(ui, texture, viewport_size, true);
+        }
+    }
+
+    fn adjustment_params(ui: &mut egui::Ui, adjustment: &mut Adjustment, width: u32, height: u32) {
+        match adjustment {
+            Adjustment::Crop {
+                x,
+                y,
+                width: w,
+                height: h,
+            } => {
+                ui.add(egui::DragValue::new(x).range(0..=width - 1).prefix("x "));
+                ui.add(egui::DragValue::new(y).range(0..=height - 1).prefix("y "));
+                ui.add(egui::DragValue::new(w).range(1..=width).prefix("w "));
+                ui.add(egui::DragValue::new(h).range(1..=height).prefix("h "));
+            }
+            Adjustment::Downscale { max_size } => {
+                ui.add(
+                    egui::DragValue::new(max_size)
+                        .range(1..=width.max(height))
+                        .suffix(" px"),
+                );
+            }
+            Adjustment::GaussianBlur { sigma } => {
+                ui.add(egui::Slider::new(sigma, 0.1..=10.0).text("σ"));
+            }
+            Adjustment::MedianBlur { radius } => {
+                ui.add(egui::Slider::new(radius, 1..=4).text("radius"));
+            }
+            Adjustment::BrightnessContrast {
+                brightness,
+                contrast,
+            } => {
+                ui.add(egui::Slider::new(brightness, -128..=128).text("brightness"));
+                ui.add(egui::Slider::new(contrast, -100.0..=100.0).text("contrast"));
+            }
+            Adjustment::Saturation { amount } => {
+                ui.add(egui::Slider::new(amount, 0.0..=2.0));
+            }
+            Adjustment::Posterize { levels } => {
+                ui.add(egui::Slider::new(levels, 2..=32).text("levels"));
+            }
+        }
+    }
+
+    fn pixelate_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let settings = &mut self.pixelate_settings;
+        ui.horizontal(|ui| {
+            let mut by_block = matches!(settings.grid, PixelGrid::BlockSize(_));
+            ui.radio_value(&mut by_block, false, "Grid");
+            ui.radio_value(&mut by_block, true, "Block size");
+            match (&mut settings.grid, by_block) {
+                (PixelGrid::Size(w, h), false) => {
+                    ui.add(egui::DragValue::new(w).range(1..=1024));
+                    ui.label("x");
+                    ui.add(egui::DragValue::new(h).range(1..=1024));
+                }
+                (PixelGrid::BlockSize(size), true) => {
+                    ui.add(egui::DragValue::new(size).range(1..=256).suffix(" px"));
+                }
+                (_, false) => settings.grid = PixelGrid::Size(64, 64),
+                (_, true) => settings.grid = PixelGrid::BlockSize(8),
+            }
+        });
+        ui.horizontal(|ui| {
+            let mode = settings.block_mode;
+            egui::ComboBox::from_label("Block color")
+                .selected_text(format!("{mode:?}"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.block_mode, BlockMode::Mode, "Mode");
+                    ui.selectable_value(&mut settings.block_mode, BlockMode::Median, "Median");
+                    ui.selectable_value(&mut settings.block_mode, BlockMode::Average, "Average");
+                });
+            ui.label("Scale");
+            ui.add(egui::DragValue::new(&mut settings.scale).range(1..=32));
+        });
+
+        ui.horizontal(|ui| {
+            if let Some(img) = &self.source_image
+                && ui.button("Pixelate").clicked()
+            {
+                let small = Pixelate::pixelate(img, &self.palette.top_rgb, &self.pixelate_settings);
+                let color_image = ColorImage::from_rgba_unmultiplied(
+                    [small.width() as usize, small.height() as usize],
+                    small.as_raw(),
+                );
+                let texture = ctx.load_texture(
+                    "pixelated_image",
+                    color_image,
+                    egui::TextureOptions::NEAREST,
+                );
+                self.pixelated = Some((small, texture));
+            }
+            if let Some((small, _)) = &self.pixelated
+                && ui.button("Export PNG…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name(format!("{}_pixelated.png", self.palette.palette_name))
+                    .save_file()
+            {
+                let big = Pixelate::upscale(small, self.pixelate_settings.scale);
+                if let Err(e) = big.save(path) {
+                    println!("Error saving pixelated image {}", e);
+                }
+            }
+        });
+
+        if self.source_image.is_none() {
+            ui.label("Open the Source view to load the image");
+        }
+        if let Some((small, texture)) = &self.pixelated {
+            let scale = self.pixelate_settings.scale as f32;
+            let size = egui::vec2(small.width() as f32 * scale, small.height() as f32 * scale);
+            egui::ScrollArea::both().max_height(600.).show(ui, |ui| {
+                ui.add(egui::Image::new(texture).fit_to_exact_size(size));
+            });
+        }
+    }
+
+    fn segments_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let (Some(img), Some(texture)) = (&self.source_image, &self.texture_id) else {
+            ui.label("Open the Source view to load the image");
+            return;
+        };
+        let settings = &mut self.segment_settings;
+        ui.horizontal(|ui| {
+            ui.label("Segments");
+            ui.add(egui::DragValue::new(&mut settings.segment_count).range(4..=400));
+            ui.label("Compactness");
+            ui.add(egui::DragValue::new(&mut settings.compactness).range(1.0..=40.0));
+            ui.label("Colors each");
+            ui.add(egui::DragValue::new(&mut settings.colors_per_segment).range(1..=16));
+            if ui.button("Segment").clicked() {
+                self.segmentation = Some(Segmentation::slic(img, settings));
+                self.selected_segments.clear();
+                self.segment_overlay = None;
+            }
+        });
+        let Some(segmentation) = &self.segmentation else {
+            return;
+        };
+
+        ui.label("Click segments to select them");
+        self.segment_viewer.controls(ui);
+        let viewport_size = egui::vec2(ui.available_width(), 500.0);
+        let viewer = self.segment_viewer.show(ui, texture, viewport_size, false);
+        let hovered = viewer
+            .response
+            .hover_pos()
+            .and_then(|pos| viewer.image_pixel(pos, img.dimensions()))
+            .and_then(|(x, y)| segmentation.segment_at(x, y));
+        if viewer.response.clicked()
+            && let Some(i) = hovered
+        {
+            match self.selected_segments.iter().position(|s| *s == i) {
+                Some(pos) => {
+                    self.selected_segments.remove(pos);
+                }
+                None => self.selected_segments.push(i),
+            }
+        }
+
+        let stale = match &self.segment_overlay {
+            Some((selected, overlay_hovered, _)) => {
+                *selected != self.selected_segments || *overlay_hovered != hovered
+            }
+            None => true,
+        };
+        if stale {
+            let overlay = segmentation.overlay(&self.selected_segments, hovered);
+            let texture = ctx.load_texture(
+                "segment_overlay",
+                ColorImage::from_rgba_unmultiplied(
+                    [overlay.width() as usize, overlay.height() as usize],
+                    overlay.as_raw(),
+                ),
+                egui::TextureOptions::NEAREST,
+            );
+            self.segment_overlay = Some((self.selected_segments.clone(), hovered, texture));
+        }
+        if let Some((_, _, overlay)) = &self.segment_overlay {
+            viewer.paint_overlay(ui, overlay);
+        }
+
+        if let Some(i) = hovered {
+            Self::segment_row(ui, &format!("Hovered #{i}"), &segmentation.segments[i]);
+        }
+        for i in &self.selected_segments {
+            Self::segment_row(ui, &format!("Segment #{i}"), &segmentation.segments[*i]);
+        }
+        if !self.selected_segments.is_empty() {
+            ui.horizontal(|ui| {
+                if ui.button("Merge selected into palette").clicked() {
+                    for i in &self.selected_segments {
+                        self.palette
+                            .merge_colors(&segmentation.segments[*i].palette);
+                    }
+                }
+                if ui.button("Clear selection").clicked() {
+                    self.selected_segments.clear();
+                }
+            });
+        }
+    }
+
+    fn saliency_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let (Some(img), Some(texture)) = (&self.source_image, &self.texture_id) else {
+            ui.label("Open the Source view to load the image");
+            return;
+        };
+        let settings = &mut self.saliency_settings;
+        ui.horizontal(|ui| {
+            ui.label("Center bias");
+            ui.add(
+                egui::DragValue::new(&mut settings.center_bias)
+                    .speed(0.01)
+                    .range(0.0..=1.0),
+            );
+            ui.label("Local contrast");
+            ui.add(
+                egui::DragValue::new(&mut settings.local_contrast)
+                    .speed(0.01)
+                    .range(0.0..=1.0),
+            );
+            ui.label("Edge density");
+            ui.add(
+                egui::DragValue::new(&mut settings.edge_density)
+                    .speed(0.01)
+                    .range(0.0..=1.0),
+            );
+            ui.label("Strength");
+            ui.add(
+                egui::DragValue::new(&mut settings.strength)
+                    .speed(0.1)
+                    .range(0.0..=32.0),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.use_mask, "Paint mask");
+            if settings.use_mask {
+                ui.selectable_value(&mut self.saliency_brush, MaskBrush::Boost, "Boost");
+                ui.selectable_value(&mut self.saliency_brush, MaskBrush::Ignore, "Ignore");
+                ui.selectable_value(&mut self.saliency_brush, MaskBrush::Erase, "Erase");
+                ui.label("Radius");
+                ui.add(egui::DragValue::new(&mut settings.brush_radius).range(1.0..=512.0));
+                if ui.button("Clear mask").clicked() {
+                    self.saliency_mask = None;
+                    self.saliency_weights = None;
+                }
+            }
+        });
+
+        let stale = match &self.saliency_weights {
+            Some((computed_for, _, _)) => *computed_for != self.saliency_settings,
+            None => true,
+        };
+        if stale && !ui.input(|i| i.pointer.any_down()) {
+            let weights =
+                Saliency::weights(img, &self.saliency_settings, self.saliency_mask.as_ref());
+            let overlay = Saliency::overlay(&weights, self.saliency_settings.strength);
+            let texture = ctx.load_texture(
+                "saliency_overlay",
+                ColorImage::from_rgba_unmultiplied(
+                    [overlay.width() as usize, overlay.height() as usize],
+                    overlay.as_raw(),
+                ),
+                egui::TextureOptions::LINEAR,
+            );
+            self.saliency_weights = Some((self.saliency_settings, weights, texture));
+        }
+
+        if let Some((_, weights, _)) = &self.saliency_weights
+            && let Some(picked_path) = &self.picked_path
+            && ui.button("Extract weighted palette").clicked()
+        {
+            self.palette.update_weighted(picked_path, img, weights);
+            self.app_state = AppState::PaletteGenerated;
+        }
+
+        self.saliency_viewer.controls(ui);
+        let viewport_size = egui::vec2(ui.available_width(), 500.0);
+        let painting = self.saliency_settings.use_mask;
+        let viewer = self
+            .saliency_viewer
+            .show(ui, texture, viewport_size, !painting);
+        if let Some((_, _, overlay)) = &self.saliency_weights {
+            viewer.paint_overlay(ui, overlay);
+        }
+        if painting
+            && viewer.response.dragged_by(egui::PointerButton::Primary)
+            && let Some(pos) = viewer.response.interact_pointer_pos()
+        {
+            let mask = self
+                .saliency_mask
+                .get_or_insert_with(|| WeightGrid::for_image(img, 1.));
+            let img_size = img.dimensions();
+            mask.paint(
+                viewer.image_pos(pos, img_size),
+                self.saliency_settings.brush_radius,
+                img_size,
+                self.saliency_brush,
+            );
+            self.saliency_weights = None;
+        }
+    }
+
+    fn segment_row(ui: &mut egui::Ui, label: &str, segment: &Segment) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{label} ({} px)", segment.pixel_count));
+            for c in &segment.palette {
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(20., 20.), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2.0, ColorUtil::rgb_to_egui(c));
+                response.on_hover_text(ColorUtil::rgb_to_hex(*c));
+            }
+        });
+    }
+
     fn color_info(ui: &mut egui::Ui, color: &Rgb<u8>) {
         let c_name = ColorNames::get_color_name(color);
         if let Some(name) = c_name {
@@ -662,9 +1953,9 @@ impl PaletteApp {
 }
 
 // Function to load an image and return it as an Rgba image
-fn load_image(path: &str) -> Result<DynamicImage, Box<dyn std::error::Error>> {
-    let img = image::open(std::path::Path::new(path))?;
-    Ok(img)
+/// Loads the image converted to sRGB, along with the color space it was tagged with.
+fn load_image(path: &str) -> Result<(DynamicImage, SourceColorSpace), Box<dyn std::error::Error>> {
+    ColorManagement::open(path)
 }
 
 fn convert_img_for_display(img: DynamicImage) -> ColorImage {
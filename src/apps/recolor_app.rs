@@ -0,0 +1,404 @@
+use eframe::egui;
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{
+    apps::PaletteApp,
+    core::{
+        color::{ColorUtil, Rgb},
+        color_space::ColorManagement,
+        color_transfer::{
+            ColorTransfer, TransferMethod, TransferReference, TransferSettings, TransferSource,
+        },
+        palette::Palette,
+        palette_map::PaletteMap,
+    },
+    widgets::custom_color_edit_button_srgba,
+};
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct RecolorApp {
+    palette_map: PaletteMap,
+    #[serde(skip_serializing, skip_deserializing)]
+    palette_list: Vec<Palette>,
+    #[serde(skip_serializing, skip_deserializing)]
+    palette_list_loaded: bool,
+    source_idx: usize,
+    target_idx: usize,
+    input_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    status: String,
+    #[serde(default)]
+    transfer_settings: TransferSettings,
+    #[serde(default)]
+    reference_idx: usize,
+    #[serde(skip_serializing, skip_deserializing)]
+    transfer_target: Option<(PathBuf, DynamicImage, egui::TextureHandle)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    transfer_reference: Option<(PathBuf, DynamicImage, egui::TextureHandle)>,
+    #[serde(skip_serializing, skip_deserializing)]
+    transfer_result: Option<(RgbaImage, egui::TextureHandle)>,
+}
+
+const SWATCH_SIZE: egui::Vec2 = egui::vec2(40., 40.);
+const PREVIEW_SIZE: f32 = 256.;
+impl eframe::App for RecolorApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| self.panels(ui, ctx));
+        });
+    }
+}
+
+impl RecolorApp {
+    fn panels(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    self.mapping_builder(ui);
+                    ui.separator();
+                    self.mapping_file_buttons(ui);
+                });
+            });
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    self.mapping_entries(ui);
+                });
+            });
+        });
+        ui.separator();
+        self.batch_panel(ui);
+        ui.separator();
+        self.transfer_panel(ui, ctx);
+    }
+
+    fn mapping_builder(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Mapping");
+        if ui.button("Reload palette list").clicked() || !self.palette_list_loaded {
+            self.palette_list = PaletteApp::load_palette_list().unwrap_or_default();
+            self.palette_list_loaded = true;
+        }
+        if self.palette_list.is_empty() {
+            ui.label("Save palettes to the list in the Palette Editor to build a mapping");
+            return;
+        }
+        Self::palette_combo(
+            ui,
+            "Source palette",
+            &self.palette_list,
+            &mut self.source_idx,
+        );
+        Self::palette_combo(
+            ui,
+            "Target palette",
+            &self.palette_list,
+            &mut self.target_idx,
+        );
+        if ui.button("Build mapping").clicked() {
+            self.palette_map = PaletteMap::from_palettes(
+                &self.palette_list[self.source_idx],
+                &self.palette_list[self.target_idx],
+            );
+        }
+    }
+
+    fn palette_combo(ui: &mut egui::Ui, label: &str, palette_list: &[Palette], idx: &mut usize) {
+        if *idx >= palette_list.len() {
+            *idx = 0;
+        }
+        egui::ComboBox::from_label(label)
+            .selected_text(palette_list[*idx].palette_name.clone())
+            .show_ui(ui, |ui| {
+                for (i, p) in palette_list.iter().enumerate() {
+                    ui.selectable_value(idx, i, p.palette_name.clone());
+                }
+            });
+    }
+
+    fn mapping_file_buttons(&mut self, ui: &mut egui::Ui) {
+        ui.text_edit_singleline(&mut self.palette_map.map_name);
+        ui.horizontal(|ui| {
+            if ui.button("Save mapping…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Palette mapping", &["json"])
+                    .set_file_name(format!("{}.json", self.palette_map.map_name))
+                    .save_file()
+            {
+                self.status = match self.palette_map.save(&path) {
+                    Ok(_) => format!("Saved mapping to {}", path.display()),
+                    Err(e) => format!("Save failed {}", e),
+                };
+            }
+            if ui.button("Load mapping…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Palette mapping", &["json"])
+                    .pick_file()
+            {
+                match PaletteMap::load(&path) {
+                    Ok(map) => self.palette_map = map,
+                    Err(e) => self.status = format!("Load failed {}", e),
+                }
+            }
+        });
+    }
+
+    fn mapping_entries(&mut self, ui: &mut egui::Ui) {
+        if self.palette_map.entries.is_empty() {
+            ui.label("No mapping");
+            return;
+        }
+        let mut remove = None;
+        egui::ScrollArea::vertical()
+            .max_height(500.)
+            .show(ui, |ui| {
+                egui::Grid::new("Palette mapping").show(ui, |ui| {
+                    for (i, (source, target)) in self.palette_map.entries.iter_mut().enumerate() {
+                        Self::swatch(ui, source);
+                        ui.label("→");
+                        let mut target_egui = ColorUtil::rgb_to_egui(target);
+                        custom_color_edit_button_srgba(ui, &mut target_egui);
+                        *target = Rgb([target_egui.r(), target_egui.g(), target_egui.b()]);
+                        ui.monospace(ColorUtil::rgb_to_hex(*target));
+                        if ui.button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        if let Some(i) = remove {
+            self.palette_map.entries.remove(i);
+        }
+    }
+
+    fn swatch(ui: &mut egui::Ui, c: &Rgb<u8>) {
+        ui.add_sized(
+            SWATCH_SIZE,
+            egui::Button::new(egui::RichText::new(ColorUtil::rgb_to_hex(*c)).small())
+                .fill(ColorUtil::rgb_to_egui(c)),
+        );
+    }
+
+    fn batch_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Batch recolor");
+        ui.horizontal(|ui| {
+            if ui.button("Input folder…").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                self.input_dir = Some(path);
+            }
+            if let Some(dir) = &self.input_dir {
+                ui.monospace(dir.display().to_string());
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Output folder…").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                self.output_dir = Some(path);
+            }
+            if let Some(dir) = &self.output_dir {
+                ui.monospace(dir.display().to_string());
+            }
+        });
+        if let (Some(input_dir), Some(output_dir)) = (&self.input_dir, &self.output_dir) {
+            let can_run = !self.palette_map.entries.is_empty() && input_dir != output_dir;
+            if ui
+                .add_enabled(can_run, egui::Button::new("Recolor folder"))
+                .clicked()
+            {
+                self.status = match self.palette_map.recolor_dir(input_dir, output_dir) {
+                    Ok(report) => {
+                        let mut status = format!("Recolored {} images", report.written);
+                        if !report.failed.is_empty() {
+                            status.push_str(&format!(", skipped {}:", report.failed.len()));
+                            for (path, e) in &report.failed {
+                                status.push_str(&format!("\n{} {}", path.display(), e));
+                            }
+                        }
+                        status
+                    }
+                    Err(e) => format!("Recolor failed {}", e),
+                };
+            }
+        }
+        ui.label(&self.status);
+    }
+
+    fn transfer_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.heading("Color transfer");
+        ui.horizontal(|ui| {
+            if ui.button("Target image…").clicked() {
+                match Self::pick_image(ctx, "transfer_target") {
+                    Ok(Some(loaded)) => {
+                        self.transfer_target = Some(loaded);
+                        self.transfer_result = None;
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.status = e,
+                }
+            }
+            if let Some((path, _, _)) = &self.transfer_target {
+                ui.monospace(path.display().to_string());
+            }
+        });
+        let settings = &mut self.transfer_settings;
+        ui.horizontal(|ui| {
+            ui.label("Reference");
+            ui.radio_value(&mut settings.reference, TransferReference::Image, "Image");
+            ui.radio_value(
+                &mut settings.reference,
+                TransferReference::SavedPalette,
+                "Saved palette",
+            );
+            match settings.reference {
+                TransferReference::Image => {
+                    if ui.button("Reference image…").clicked() {
+                        match Self::pick_image(ctx, "transfer_reference") {
+                            Ok(Some(loaded)) => self.transfer_reference = Some(loaded),
+                            Ok(None) => {}
+                            Err(e) => self.status = e,
+                        }
+                    }
+                    if let Some((path, _, _)) = &self.transfer_reference {
+                        ui.monospace(path.display().to_string());
+                    }
+                }
+                TransferReference::SavedPalette if self.palette_list.is_empty() => {
+                    ui.label("No saved palettes");
+                }
+                TransferReference::SavedPalette => Self::palette_combo(
+                    ui,
+                    "Reference palette",
+                    &self.palette_list,
+                    &mut self.reference_idx,
+                ),
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut settings.method,
+                TransferMethod::MeanStd,
+                "Mean/std (Reinhard)",
+            );
+            ui.radio_value(&mut settings.method, TransferMethod::Palette, "Palette");
+            if settings.method == TransferMethod::Palette {
+                ui.label("Colors");
+                ui.add(egui::DragValue::new(&mut settings.palette_size).range(2..=32));
+            }
+            ui.add(egui::Slider::new(&mut settings.strength, 0.0..=1.0).text("Strength"));
+        });
+
+        let has_reference = match self.transfer_settings.reference {
+            TransferReference::Image => self.transfer_reference.is_some(),
+            TransferReference::SavedPalette => self.reference_idx < self.palette_list.len(),
+        };
+        ui.horizontal(|ui| {
+            if let Some((_, target, _)) = &self.transfer_target
+                && has_reference
+                && ui.button("Transfer").clicked()
+            {
+                // Built on click only, both sources scan every pixel or entry
+                let reference = match self.transfer_settings.reference {
+                    TransferReference::Image => self
+                        .transfer_reference
+                        .as_ref()
+                        .map(|(_, img, _)| TransferSource::from_image(img)),
+                    TransferReference::SavedPalette => self
+                        .palette_list
+                        .get(self.reference_idx)
+                        .map(TransferSource::from_palette),
+                };
+                let Some(reference) = reference else {
+                    return;
+                };
+                let out = ColorTransfer::transfer(target, &reference, &self.transfer_settings);
+                let texture = ctx.load_texture(
+                    "transfer_result",
+                    egui::ColorImage::from_rgba_unmultiplied(
+                        [out.width() as usize, out.height() as usize],
+                        out.as_raw(),
+                    ),
+                    egui::TextureOptions::LINEAR,
+                );
+                self.transfer_result = Some((out, texture));
+            }
+            if let Some((out, _)) = &self.transfer_result
+                && ui.button("Export PNG…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name("transfer.png")
+                    .save_file()
+            {
+                self.status = match out.save(&path) {
+                    Ok(_) => format!("Saved {}", path.display()),
+                    Err(e) => format!("Export failed {}", e),
+                };
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if let Some((_, _, texture)) = &self.transfer_target {
+                Self::preview(ui, "Target", texture);
+            }
+            match self.transfer_settings.reference {
+                TransferReference::Image => {
+                    if let Some((_, _, texture)) = &self.transfer_reference {
+                        Self::preview(ui, "Reference", texture);
+                    }
+                }
+                TransferReference::SavedPalette => {
+                    if let Some(palette) = self.palette_list.get(self.reference_idx) {
+                        ui.vertical(|ui| {
+                            ui.label("Reference");
+                            for c in &palette.top_rgb {
+                                Self::swatch(ui, c);
+                            }
+                        });
+                    }
+                }
+            }
+            if let Some((_, texture)) = &self.transfer_result {
+                Self::preview(ui, "Result", texture);
+            }
+        });
+    }
+
+    /// `Ok(None)` when the dialog is cancelled, the error is for the status line.
+    fn pick_image(
+        ctx: &egui::Context,
+        name: &str,
+    ) -> Result<Option<(PathBuf, DynamicImage, egui::TextureHandle)>, String> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Image", &["png", "jpg", "jpeg", "webp"])
+            .pick_file()
+        else {
+            return Ok(None);
+        };
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| format!("Load failed, unsupported path {}", path.display()))?;
+        let (img, _) = ColorManagement::open(path_str)
+            .map_err(|e| format!("Load failed {} {}", path.display(), e))?;
+        let rgba = img.to_rgba8();
+        let texture = ctx.load_texture(
+            name,
+            egui::ColorImage::from_rgba_unmultiplied(
+                [rgba.width() as usize, rgba.height() as usize],
+                rgba.as_raw(),
+            ),
+            egui::TextureOptions::LINEAR,
+        );
+        Ok(Some((path, img, texture)))
+    }
+
+    fn preview(ui: &mut egui::Ui, label: &str, texture: &egui::TextureHandle) {
+        ui.vertical(|ui| {
+            ui.label(label);
+            let size = texture.size_vec2();
+            let scale = (PREVIEW_SIZE / size.x.max(size.y)).min(1.);
+            ui.add(egui::Image::new(texture).fit_to_exact_size(size * scale));
+        });
+    }
+}
@@ -3,7 +3,7 @@ use eframe::egui;
 // mod core;
 // mod apps;
 // mod debug;
-use crate::apps::{ColorApp, ColorWheelApp, PaintApp, PaletteApp};
+use crate::apps::{ColorApp, ColorWheelApp, PaintApp, PaletteApp, RecolorApp};
 
 use crate::debug::backend_panel;
 
@@ -14,6 +14,7 @@ pub enum Anchor {
     ColorEditor,
     ColorWheel,
     Paint,
+    Recolor,
 }
 #[derive(Default, serde::Deserialize, serde::Serialize)]
 // #[cfg_attr(serde(default)]
@@ -22,6 +23,8 @@ pub struct State {
     color_app: ColorApp,
     color_wheel_app: ColorWheelApp,
     paint_app: PaintApp,
+    #[serde(default)]
+    recolor_app: RecolorApp,
     selected_anchor: Anchor,
     #[serde(skip_serializing, skip_deserializing)]
     backend_panel: backend_panel::BackendPanel,
@@ -76,6 +79,11 @@ impl WrapApp {
                 Anchor::Paint,
                 &mut self.state.paint_app as &mut dyn eframe::App,
             ),
+            (
+                "Recolor",
+                Anchor::Recolor,
+                &mut self.state.recolor_app as &mut dyn eframe::App,
+            ),
         ];
 
         vec.into_iter()
@@ -0,0 +1,196 @@
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+
+/// A single non-destructive edit applied to the source before extraction.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum Adjustment {
+    /// Crop rect in source pixels, clipped to the image.
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Shrinks so the longest side is at most `max_size`, keeping the aspect ratio.
+    Downscale {
+        max_size: u32,
+    },
+    GaussianBlur {
+        sigma: f32,
+    },
+    /// Per channel median over a (2 * radius + 1) square, good for jpeg noise.
+    MedianBlur {
+        radius: u32,
+    },
+    /// `brightness` is added to every channel, `contrast` in percent.
+    BrightnessContrast {
+        brightness: i32,
+        contrast: f32,
+    },
+    /// 0 is greyscale, 1 unchanged, above 1 more saturated.
+    Saturation {
+        amount: f32,
+    },
+    /// Number of levels kept per channel.
+    Posterize {
+        levels: u8,
+    },
+}
+
+impl Adjustment {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Adjustment::Crop { .. } => "Crop",
+            Adjustment::Downscale { .. } => "Downscale",
+            Adjustment::GaussianBlur { .. } => "Gaussian blur",
+            Adjustment::MedianBlur { .. } => "Median blur",
+            Adjustment::BrightnessContrast { .. } => "Brightness/contrast",
+            Adjustment::Saturation { .. } => "Saturation",
+            Adjustment::Posterize { .. } => "Posterize",
+        }
+    }
+
+    /// One of each kind with neutral-ish defaults, used to populate the add menu.
+    pub fn all_defaults(width: u32, height: u32) -> Vec<Adjustment> {
+        vec![
+            Adjustment::Crop {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            },
+            Adjustment::Downscale { max_size: 256 },
+            Adjustment::GaussianBlur { sigma: 1. },
+            Adjustment::MedianBlur { radius: 1 },
+            Adjustment::BrightnessContrast {
+                brightness: 0,
+                contrast: 0.,
+            },
+            Adjustment::Saturation { amount: 1. },
+            Adjustment::Posterize { levels: 8 },
+        ]
+    }
+
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        match *self {
+            Adjustment::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let x = x.min(img.width().saturating_sub(1));
+                let y = y.min(img.height().saturating_sub(1));
+                let width = width.clamp(1, img.width() - x);
+                let height = height.clamp(1, img.height() - y);
+                img.crop_imm(x, y, width, height)
+            }
+            Adjustment::Downscale { max_size } => {
+                let max_size = max_size.max(1);
+                if img.width() <= max_size && img.height() <= max_size {
+                    img.clone()
+                } else {
+                    // Averaging neighbors also smooths out noise
+                    img.resize(max_size, max_size, imageops::FilterType::Triangle)
+                }
+            }
+            Adjustment::GaussianBlur { sigma } => img.blur(sigma.max(0.01)),
+            Adjustment::MedianBlur { radius } => {
+                DynamicImage::ImageRgba8(Self::median(&img.to_rgba8(), radius))
+            }
+            Adjustment::BrightnessContrast {
+                brightness,
+                contrast,
+            } => img.brighten(brightness).adjust_contrast(contrast),
+            Adjustment::Saturation { amount } => {
+                let mut rgba = img.to_rgba8();
+                for p in rgba.pixels_mut() {
+                    let luma = 0.2126 * p[0] as f32 + 0.7152 * p[1] as f32 + 0.0722 * p[2] as f32;
+                    for i in 0..3 {
+                        p[i] = (luma + (p[i] as f32 - luma) * amount).clamp(0., 255.) as u8;
+                    }
+                }
+                DynamicImage::ImageRgba8(rgba)
+            }
+            Adjustment::Posterize { levels } => {
+                let levels = levels.max(2) as f32 - 1.;
+                let mut rgba = img.to_rgba8();
+                for p in rgba.pixels_mut() {
+                    for i in 0..3 {
+                        let v = (p[i] as f32 / 255. * levels).round() / levels;
+                        p[i] = (v * 255.).round() as u8;
+                    }
+                }
+                DynamicImage::ImageRgba8(rgba)
+            }
+        }
+    }
+
+    fn median(img: &RgbaImage, radius: u32) -> RgbaImage {
+        let (width, height) = img.dimensions();
+        let r = radius as i64;
+        let mut out = RgbaImage::new(width, height);
+        let mut window: [Vec<u8>; 4] = Default::default();
+        for y in 0..height {
+            for x in 0..width {
+                for channel in window.iter_mut() {
+                    channel.clear();
+                }
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        let px = (x as i64 + dx).clamp(0, width as i64 - 1) as u32;
+                        let py = (y as i64 + dy).clamp(0, height as i64 - 1) as u32;
+                        let p = img.get_pixel(px, py);
+                        for (i, channel) in window.iter_mut().enumerate() {
+                            channel.push(p[i]);
+                        }
+                    }
+                }
+                let mut median = [0u8; 4];
+                for (i, channel) in window.iter_mut().enumerate() {
+                    let mid = channel.len() / 2;
+                    median[i] = *channel.select_nth_unstable(mid).1;
+                }
+                out.put_pixel(x, y, Rgba(median));
+            }
+        }
+        out
+    }
+}
+
+/// Ordered list of adjustments, each of which can be toggled off.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub(crate) struct AdjustmentStack {
+    pub adjustments: Vec<(bool, Adjustment)>,
+}
+
+impl AdjustmentStack {
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let mut out = img.clone();
+        for (enabled, adjustment) in &self.adjustments {
+            if *enabled {
+                out = adjustment.apply(&out);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_removes_speckle() {
+        let mut img = RgbaImage::from_pixel(3, 3, Rgba([100, 100, 100, 255]));
+        img.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        let stack = AdjustmentStack {
+            adjustments: vec![
+                (true, Adjustment::MedianBlur { radius: 1 }),
+                (false, Adjustment::Posterize { levels: 2 }),
+            ],
+        };
+        let out = stack.apply(&DynamicImage::ImageRgba8(img)).to_rgba8();
+        assert_eq!(out.get_pixel(1, 1), &Rgba([100, 100, 100, 255]));
+    }
+}
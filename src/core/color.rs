@@ -6,6 +6,8 @@ use egui::epaint::Hsva;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use crate::core::matrix::{LINEAR_SRGB_TO_XYZ, XYZ_TO_LINEAR_SRGB, mul};
+
 // pub struct PRgb {}
 #[derive(Deserialize, Serialize, Clone, Copy, Eq, Hash, PartialEq, Debug)]
 pub struct Rgb<T>(pub [T; 3]);
@@ -27,9 +29,9 @@ impl Index<usize> for Rgb<f32> {
 
 #[derive(Serialize, Deserialize)]
 pub struct HSV {
-    h: f32,
-    s: f32,
-    v: f32,
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
 }
 
 impl HSV {
@@ -131,6 +133,60 @@ impl HSV {
     // fn rgb_prime(h: f32, c: u)
 }
 
+/// CIE L*a*b* (D65) used for perceptual color differences.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Lab {
+    pub fn from_rgb(c: Rgb<u8>) -> Self {
+        let [x, y, z] = mul(&LINEAR_SRGB_TO_XYZ, c.0.map(ColorUtil::srgb_to_linear));
+        // Normalized by the D65 white point
+        let (x, y, z) = (x / 0.95047, y, z / 1.08883);
+        let f = |t: f32| {
+            if t > 0.008856 {
+                t.cbrt()
+            } else {
+                7.787 * t + 16. / 116.
+            }
+        };
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+        Self {
+            l: 116. * fy - 16.,
+            a: 500. * (fx - fy),
+            b: 200. * (fy - fz),
+        }
+    }
+
+    /// Inverse of `from_rgb`, out of gamut colors are clipped.
+    pub fn to_rgb(&self) -> Rgb<u8> {
+        let fy = (self.l + 16.) / 116.;
+        let fx = fy + self.a / 500.;
+        let fz = fy - self.b / 200.;
+        let f_inv = |t: f32| {
+            let t3 = t * t * t;
+            if t3 > 0.008856 {
+                t3
+            } else {
+                (t - 16. / 116.) / 7.787
+            }
+        };
+        let xyz = [f_inv(fx) * 0.95047, f_inv(fy), f_inv(fz) * 1.08883];
+        Rgb(mul(&XYZ_TO_LINEAR_SRGB, xyz).map(ColorUtil::linear_to_srgb))
+    }
+
+    /// CIE76 color difference.
+    pub fn delta_e(&self, other: &Lab) -> f32 {
+        let dl = self.l - other.l;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        f32::sqrt(dl * dl + da * da + db * db)
+    }
+}
+
 pub(crate) struct ColorUtil {}
 
 impl ColorUtil {
@@ -164,10 +220,80 @@ impl ColorUtil {
         egui::Color32::from_rgb(c[0], c[1], c[2])
     }
 
+    pub fn srgb_to_linear(c: u8) -> f32 {
+        let c = c as f32 / 255.;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    pub fn linear_to_srgb(c: f32) -> u8 {
+        let c = c.clamp(0., 1.);
+        let s = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1. / 2.4) - 0.055
+        };
+        (s * 255.).round() as u8
+    }
+
+    /// Naive conversion without an ink profile, all channels 0-1.
+    pub fn cmyk_to_rgb(c: f32, m: f32, y: f32, k: f32) -> Rgb<u8> {
+        let channel = |v: f32| (255. * (1. - v) * (1. - k)).round().clamp(0., 255.) as u8;
+        Rgb([channel(c), channel(m), channel(y)])
+    }
+
+    /// Inverse of `cmyk_to_rgb` with full black generation, channels 0-1.
+    pub fn rgb_to_cmyk(c: Rgb<u8>) -> [f32; 4] {
+        let [r, g, b] = c.0.map(|v| v as f32 / 255.);
+        let k = 1. - r.max(g).max(b);
+        if k >= 1. {
+            return [0., 0., 0., 1.];
+        }
+        [
+            (1. - r - k) / (1. - k),
+            (1. - g - k) / (1. - k),
+            (1. - b - k) / (1. - k),
+            k,
+        ]
+    }
+
     fn component_diff(c1: Rgb<u8>, c2: Rgb<u8>, component: usize) -> f32 {
         (c1[component] as f32) - (c2[component] as f32)
     }
 
+    /// Closest color in `palette`, or `c` itself when the palette is empty.
+    pub fn nearest_color(c: Rgb<u8>, palette: &[Rgb<u8>]) -> Rgb<u8> {
+        palette
+            .iter()
+            .min_by(|a, b| Self::color_distance(c, **a).total_cmp(&Self::color_distance(c, **b)))
+            .copied()
+            .unwrap_or(c)
+    }
+
+    /// Black or white, whichever reads better on top of `c`.
+    pub fn contrast_text(c: &Rgb<u8>) -> egui::Color32 {
+        if Lab::from_rgb(*c).l > 55. {
+            egui::Color32::BLACK
+        } else {
+            egui::Color32::WHITE
+        }
+    }
+
+    /// WCAG 2 relative luminance, 0 for black to 1 for white.
+    pub fn relative_luminance(c: Rgb<u8>) -> f32 {
+        let [r, g, b] = c.0.map(Self::srgb_to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// WCAG 2 contrast ratio between 1 and 21, in either order.
+    pub fn contrast_ratio(a: Rgb<u8>, b: Rgb<u8>) -> f32 {
+        let (la, lb) = (Self::relative_luminance(a), Self::relative_luminance(b));
+        (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+    }
+
     pub fn rgb_to_hex(color: Rgb<u8>) -> String {
         format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2])
     }
@@ -263,6 +389,31 @@ mod tests {
         rgb_test(Rgb([55, 0, 255]));
     }
 
+    #[test]
+    fn lab_white_and_black() {
+        let white = Lab::from_rgb(Rgb([255, 255, 255]));
+        let black = Lab::from_rgb(Rgb([0, 0, 0]));
+        assert!((white.l - 100.).abs() < 0.1);
+        assert!(white.a.abs() < 0.1 && white.b.abs() < 0.1);
+        assert!((white.delta_e(&black) - 100.).abs() < 0.1);
+    }
+
+    #[test]
+    fn lab_round_trip() {
+        for c in [Rgb([255, 255, 255]), Rgb([0, 0, 0]), Rgb([12, 200, 99])] {
+            assert_eq!(Lab::from_rgb(c).to_rgb(), c);
+        }
+    }
+
+    #[test]
+    fn wcag_contrast() {
+        let white = Rgb([255, 255, 255]);
+        let black = Rgb([0, 0, 0]);
+        assert!((ColorUtil::contrast_ratio(white, black) - 21.).abs() < 0.01);
+        assert!((ColorUtil::contrast_ratio(black, Rgb([119, 119, 119])) - 4.69).abs() < 0.01);
+        assert_eq!(ColorUtil::contrast_ratio(white, white), 1.);
+    }
+
     fn rgb_test(rgb: Rgb<u8>) {
         let h = HSV::from_rgb_u(rgb);
         valid_hsv(&h);
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::core::color::{ColorUtil, HSV, Rgb};
+use crate::core::gamut::WideColor;
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ColorDetail {
@@ -14,6 +15,9 @@ pub(crate) struct ColorDetail {
     pub split_complement: (Rgb<u8>, Rgb<u8>),
     pub split_complement_egui: (egui::Color32, egui::Color32),
     pub split_complement_hex: (String, String),
+    /// Set when picked in a wide-gamut mode, `color` is then the clipped sRGB version.
+    #[serde(default)]
+    pub wide: Option<WideColor>,
 }
 
 impl ColorDetail {
@@ -40,6 +44,7 @@ impl ColorDetail {
             split_complement,
             split_complement_egui,
             split_complement_hex,
+            wide: None,
         }
     }
 
@@ -59,6 +64,7 @@ impl ColorDetail {
                 self.egui_color.b(),
             ]);
             self.hex = ColorUtil::rgb_to_hex(self.color);
+            self.wide = None;
             if update_complement {
                 let complement = ColorUtil::get_compliment(self.color);
                 self.complement_hex = ColorUtil::rgb_to_hex(complement);
@@ -79,4 +85,15 @@ impl ColorDetail {
             }
         }
     }
+
+    pub fn set_wide(&mut self, wide: WideColor) {
+        self.egui_color = ColorUtil::rgb_to_egui(&wide.to_srgb());
+        self.update_from_egui_color(true);
+        self.wide = Some(wide);
+    }
+
+    /// True when the picked wide-gamut color can't be shown exactly in sRGB.
+    pub fn out_of_srgb(&self) -> bool {
+        self.wide.is_some_and(|w| !w.in_srgb())
+    }
 }
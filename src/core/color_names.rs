@@ -1,4 +1,7 @@
-use crate::{COLOR_DICT, core::color::Rgb};
+use crate::{
+    COLOR_DICT, COLOR_LABS,
+    core::color::{Lab, Rgb},
+};
 use std::{collections::HashMap, error::Error, fs::File};
 
 pub struct ColorNames {}
@@ -27,6 +30,13 @@ impl ColorNames {
         Ok(map)
     }
 
+    /// Lab value of every named color, computed once when the names load.
+    pub fn labs(map: &HashMap<Rgb<u8>, String>) -> Vec<(Lab, String)> {
+        map.iter()
+            .map(|(c, name)| (Lab::from_rgb(*c), name.clone()))
+            .collect()
+    }
+
     pub fn get_color_name(color: &Rgb<u8>) -> Option<String> {
         // let r = color[0];
         // let g = color[1];
@@ -38,4 +48,18 @@ impl ColorNames {
         // println!("color name {reference}");
         Some(reference.to_string())
     }
+
+    /// Name of the closest named color by ΔE, `None` before the names are loaded.
+    pub fn nearest_color_name(color: &Rgb<u8>) -> Option<String> {
+        if let Some(name) = COLOR_DICT.lock().unwrap().get(color) {
+            return Some(name.to_string());
+        }
+        let lab = Lab::from_rgb(*color);
+        COLOR_LABS
+            .lock()
+            .unwrap()
+            .iter()
+            .min_by(|a, b| lab.delta_e(&a.0).total_cmp(&lab.delta_e(&b.0)))
+            .map(|(_, name)| name.to_string())
+    }
 }
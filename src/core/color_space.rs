@@ -0,0 +1,394 @@
+use image::codecs::png::PngDecoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::core::color::ColorUtil;
+use crate::core::matrix::{Matrix, mul, mul_matrix};
+
+/// XYZ (D50, the ICC connection space) to linear sRGB, Bradford adapted.
+const XYZ_D50_TO_SRGB: Matrix = [
+    [3.133_856, -1.616_866_7, -0.490_614_6],
+    [-0.978_768_4, 1.916_141_5, 0.033_454],
+    [0.071_945_3, -0.228_991_4, 1.405_242_7],
+];
+
+/// Red colorants (D50) of the matrix profiles we recognize by value.
+const SRGB_RED_XYZ: [f32; 3] = [0.4361, 0.2225, 0.0139];
+const DISPLAY_P3_RED_XYZ: [f32; 3] = [0.5151, 0.2412, -0.0011];
+const ADOBE_RGB_RED_XYZ: [f32; 3] = [0.6097, 0.3111, 0.0195];
+
+/// PNG gAMA values this close to 1/2.2 are treated as plain sRGB.
+const SRGB_GAMMA_TOLERANCE: f64 = 0.01;
+
+/// Color space an image was encoded in, as far as we can tell from its metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub enum SourceColorSpace {
+    /// No profile or gamma, assumed to be sRGB.
+    #[default]
+    Untagged,
+    Srgb,
+    DisplayP3,
+    AdobeRgb,
+    /// Some other RGB matrix profile, by its description.
+    OtherIcc(String),
+    /// An ICC profile we can't convert from, e.g. LUT based or CMYK.
+    Unsupported(String),
+    /// PNG gAMA chunk without a profile.
+    Gamma(f32),
+}
+
+impl SourceColorSpace {
+    pub fn label(&self) -> String {
+        match self {
+            SourceColorSpace::Untagged => "Untagged (assumed sRGB)".to_string(),
+            SourceColorSpace::Srgb => "sRGB".to_string(),
+            SourceColorSpace::DisplayP3 => "Display P3, converted to sRGB".to_string(),
+            SourceColorSpace::AdobeRgb => "Adobe RGB (1998), converted to sRGB".to_string(),
+            SourceColorSpace::OtherIcc(name) => format!("{name}, converted to sRGB"),
+            SourceColorSpace::Unsupported(name) => format!("{name}, not converted"),
+            SourceColorSpace::Gamma(g) => format!("Gamma {g:.3}, converted to sRGB"),
+        }
+    }
+}
+
+/// Tone response curve of one ICC channel.
+#[derive(Clone, Debug, PartialEq)]
+enum Trc {
+    Gamma(f32),
+    Table(Vec<f32>),
+    /// ICC parametric curve: function type and its parameters.
+    Parametric(u16, Vec<f32>),
+}
+
+impl Trc {
+    fn to_linear(&self, v: f32) -> f32 {
+        match self {
+            Trc::Gamma(g) => v.powf(*g),
+            Trc::Table(table) => {
+                let pos = v * (table.len() - 1) as f32;
+                let i = (pos.floor() as usize).min(table.len() - 2);
+                let t = pos - i as f32;
+                table[i] + (table[i + 1] - table[i]) * t
+            }
+            Trc::Parametric(kind, p) => {
+                let (g, a, b, c, d, e, f) = (
+                    p[0],
+                    p.get(1).copied().unwrap_or(1.),
+                    p.get(2).copied().unwrap_or(0.),
+                    p.get(3).copied().unwrap_or(0.),
+                    p.get(4).copied().unwrap_or(0.),
+                    p.get(5).copied().unwrap_or(0.),
+                    p.get(6).copied().unwrap_or(0.),
+                );
+                let curve = |x: f32| x.max(0.).powf(g);
+                match kind {
+                    0 => v.powf(g),
+                    1 if v >= -b / a => curve(a * v + b),
+                    1 => 0.,
+                    2 if v >= -b / a => curve(a * v + b) + c,
+                    2 => c,
+                    3 if v >= d => curve(a * v + b),
+                    3 => c * v,
+                    4 if v >= d => curve(a * v + b) + e,
+                    4 => c * v + f,
+                    _ => v.powf(g),
+                }
+            }
+        }
+    }
+}
+
+/// The parts of an RGB matrix/TRC ICC profile needed to convert to sRGB.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct IccProfile {
+    pub description: String,
+    /// Columns are the red, green and blue colorants in XYZ (D50).
+    to_xyz: Matrix,
+    trc: [Trc; 3],
+}
+
+impl IccProfile {
+    /// Parses an RGB matrix profile, `Err` holds the description of other profiles.
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let description = Self::find_tag(bytes, b"desc")
+            .and_then(Self::parse_text)
+            .unwrap_or_else(|| "Unknown ICC profile".to_string());
+        if bytes.len() < 132 || &bytes[16..20] != b"RGB " {
+            return Err(description);
+        }
+        let colorants = [b"rXYZ", b"gXYZ", b"bXYZ"]
+            .map(|sig| Self::find_tag(bytes, sig).and_then(Self::parse_xyz));
+        let trcs = [b"rTRC", b"gTRC", b"bTRC"]
+            .map(|sig| Self::find_tag(bytes, sig).and_then(Self::parse_trc));
+        let ([Some(r), Some(g), Some(b)], [Some(r_trc), Some(g_trc), Some(b_trc)]) =
+            (colorants, trcs)
+        else {
+            return Err(description);
+        };
+        Ok(Self {
+            description,
+            to_xyz: [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]],
+            trc: [r_trc, g_trc, b_trc],
+        })
+    }
+
+    pub fn classify(&self) -> SourceColorSpace {
+        let red = [self.to_xyz[0][0], self.to_xyz[1][0], self.to_xyz[2][0]];
+        let close = |known: [f32; 3]| (0..3).all(|i| (red[i] - known[i]).abs() < 0.01);
+        if close(SRGB_RED_XYZ) {
+            SourceColorSpace::Srgb
+        } else if close(DISPLAY_P3_RED_XYZ) {
+            SourceColorSpace::DisplayP3
+        } else if close(ADOBE_RGB_RED_XYZ) {
+            SourceColorSpace::AdobeRgb
+        } else {
+            SourceColorSpace::OtherIcc(self.description.clone())
+        }
+    }
+
+    fn find_tag<'a>(bytes: &'a [u8], sig: &[u8; 4]) -> Option<&'a [u8]> {
+        let count = read_u32(bytes, 128)? as usize;
+        for i in 0..count {
+            let entry = 132 + i * 12;
+            if bytes.get(entry..entry + 4)? == sig {
+                let offset = read_u32(bytes, entry + 4)? as usize;
+                let size = read_u32(bytes, entry + 8)? as usize;
+                return bytes.get(offset..offset + size);
+            }
+        }
+        None
+    }
+
+    fn parse_xyz(tag: &[u8]) -> Option<[f32; 3]> {
+        if tag.get(0..4)? != b"XYZ " {
+            return None;
+        }
+        Some([
+            read_s15_fixed16(tag, 8)?,
+            read_s15_fixed16(tag, 12)?,
+            read_s15_fixed16(tag, 16)?,
+        ])
+    }
+
+    fn parse_trc(tag: &[u8]) -> Option<Trc> {
+        match tag.get(0..4)? {
+            b"curv" => {
+                let count = read_u32(tag, 8)? as usize;
+                match count {
+                    0 => Some(Trc::Gamma(1.)),
+                    1 => Some(Trc::Gamma(read_u16(tag, 12)? as f32 / 256.)),
+                    _ => {
+                        let table = (0..count)
+                            .map(|i| Some(read_u16(tag, 12 + i * 2)? as f32 / 65535.))
+                            .collect::<Option<Vec<f32>>>()?;
+                        Some(Trc::Table(table))
+                    }
+                }
+            }
+            b"para" => {
+                let kind = read_u16(tag, 8)?;
+                let param_count = match kind {
+                    0 => 1,
+                    1 => 3,
+                    2 => 4,
+                    3 => 5,
+                    4 => 7,
+                    _ => return None,
+                };
+                let params = (0..param_count)
+                    .map(|i| read_s15_fixed16(tag, 12 + i * 4))
+                    .collect::<Option<Vec<f32>>>()?;
+                Some(Trc::Parametric(kind, params))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_text(tag: &[u8]) -> Option<String> {
+        match tag.get(0..4)? {
+            // v2 textDescriptionType: ascii count then characters
+            b"desc" => {
+                let count = read_u32(tag, 8)? as usize;
+                let text = tag.get(12..12 + count)?;
+                Some(
+                    String::from_utf8_lossy(text)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                )
+            }
+            // v4 multiLocalizedUnicodeType: take the first record
+            b"mluc" => {
+                let length = read_u32(tag, 20)? as usize;
+                let offset = read_u32(tag, 24)? as usize;
+                let utf16: Vec<u16> = tag
+                    .get(offset..offset + length)?
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&utf16))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_s15_fixed16(bytes: &[u8], offset: usize) -> Option<f32> {
+    let raw = i32::from_be_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?);
+    Some(raw as f32 / 65536.)
+}
+
+/// Loads images and converts them to sRGB from their embedded color space.
+pub(crate) struct ColorManagement {}
+
+impl ColorManagement {
+    pub fn open(path: &str) -> Result<(DynamicImage, SourceColorSpace), Box<dyn Error>> {
+        let reader = ImageReader::open(path)?.with_guessed_format()?;
+        // From the content, so a PNG with another extension still gets its gamma
+        let format = reader.format();
+        let mut decoder = reader.into_decoder()?;
+        let icc = decoder.icc_profile()?;
+        let img = DynamicImage::from_decoder(decoder)?;
+
+        if let Some(bytes) = icc {
+            return Ok(match IccProfile::parse(&bytes) {
+                Ok(profile) => {
+                    let space = profile.classify();
+                    if space == SourceColorSpace::Srgb {
+                        (img, space)
+                    } else {
+                        (Self::icc_to_srgb(&img, &profile), space)
+                    }
+                }
+                Err(description) => (img, SourceColorSpace::Unsupported(description)),
+            });
+        }
+
+        if format == Some(ImageFormat::Png) {
+            let png = PngDecoder::new(BufReader::new(File::open(Path::new(path))?))?;
+            if let Some(gamma) = png.gamma_value()?
+                && (gamma - 1. / 2.2).abs() > SRGB_GAMMA_TOLERANCE
+            {
+                let gamma = gamma as f32;
+                let converted = Self::map_linear(&img, |c| c.map(|v| v.powf(1. / gamma)));
+                return Ok((converted, SourceColorSpace::Gamma(gamma)));
+            }
+        }
+
+        Ok((img, SourceColorSpace::Untagged))
+    }
+
+    pub fn icc_to_srgb(img: &DynamicImage, profile: &IccProfile) -> DynamicImage {
+        let to_srgb = mul_matrix(&XYZ_D50_TO_SRGB, &profile.to_xyz);
+        let luts: Vec<[f32; 256]> = profile
+            .trc
+            .iter()
+            .map(|trc| std::array::from_fn(|i| trc.to_linear(i as f32 / 255.)))
+            .collect();
+        let mut rgba = img.to_rgba8();
+        for p in rgba.pixels_mut() {
+            let linear = [
+                luts[0][p[0] as usize],
+                luts[1][p[1] as usize],
+                luts[2][p[2] as usize],
+            ];
+            let srgb = mul(&to_srgb, linear);
+            p[0] = ColorUtil::linear_to_srgb(srgb[0]);
+            p[1] = ColorUtil::linear_to_srgb(srgb[1]);
+            p[2] = ColorUtil::linear_to_srgb(srgb[2]);
+        }
+        DynamicImage::ImageRgba8(rgba)
+    }
+
+    /// Applies `decode` to normalized channel values and re-encodes as sRGB.
+    fn map_linear(img: &DynamicImage, decode: impl Fn([f32; 3]) -> [f32; 3]) -> DynamicImage {
+        let mut rgba: RgbaImage = img.to_rgba8();
+        for p in rgba.pixels_mut() {
+            let linear = decode([p[0] as f32 / 255., p[1] as f32 / 255., p[2] as f32 / 255.]);
+            p[0] = ColorUtil::linear_to_srgb(linear[0]);
+            p[1] = ColorUtil::linear_to_srgb(linear[1]);
+            p[2] = ColorUtil::linear_to_srgb(linear[2]);
+        }
+        DynamicImage::ImageRgba8(rgba)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag_xyz(v: [f32; 3]) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for c in v {
+            tag.extend(((c * 65536.).round() as i32).to_be_bytes());
+        }
+        tag
+    }
+
+    /// Minimal profile with the given colorants and a 2.2 gamma curve.
+    fn profile(colorants: [[f32; 3]; 3]) -> Vec<u8> {
+        let mut gamma = b"curv\0\0\0\0".to_vec();
+        gamma.extend(1u32.to_be_bytes());
+        gamma.extend(((2.2f32 * 256.) as u16).to_be_bytes());
+        gamma.extend([0, 0]);
+        let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+            (b"rXYZ", tag_xyz(colorants[0])),
+            (b"gXYZ", tag_xyz(colorants[1])),
+            (b"bXYZ", tag_xyz(colorants[2])),
+            (b"rTRC", gamma.clone()),
+            (b"gTRC", gamma.clone()),
+            (b"bTRC", gamma),
+        ];
+        let mut bytes = vec![0u8; 128];
+        bytes[16..20].copy_from_slice(b"RGB ");
+        bytes.extend((tags.len() as u32).to_be_bytes());
+        let mut offset = 132 + tags.len() * 12;
+        let mut data: Vec<u8> = Vec::new();
+        for (sig, tag) in &tags {
+            bytes.extend(*sig);
+            bytes.extend((offset as u32).to_be_bytes());
+            bytes.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            data.extend(tag);
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn display_p3_red_is_out_of_srgb() {
+        let bytes = profile([
+            DISPLAY_P3_RED_XYZ,
+            [0.2920, 0.6922, 0.0419],
+            [0.1571, 0.0666, 0.7841],
+        ]);
+        let profile = IccProfile::parse(&bytes).unwrap();
+        assert_eq!(profile.classify(), SourceColorSpace::DisplayP3);
+        let mut img = RgbaImage::from_pixel(2, 1, image::Rgba([255, 255, 255, 255]));
+        img.put_pixel(1, 0, image::Rgba([255, 0, 0, 255]));
+        let out = ColorManagement::icc_to_srgb(&DynamicImage::ImageRgba8(img), &profile).to_rgba8();
+        // White stays white
+        let p = out.get_pixel(0, 0);
+        assert!(p[0] >= 253 && p[1] >= 253 && p[2] >= 253);
+        // Pure P3 red is more saturated than sRGB allows and clips to sRGB red
+        let unclipped = mul(&mul_matrix(&XYZ_D50_TO_SRGB, &profile.to_xyz), [1., 0., 0.]);
+        assert!(unclipped[0] > 1.05 && unclipped[1] < 0. && unclipped[2] < 0.);
+        let p = out.get_pixel(1, 0);
+        assert!(p[0] >= 253 && p[1] <= 2 && p[2] <= 2, "{p:?}");
+    }
+}
@@ -0,0 +1,248 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::{
+    color::{Lab, Rgb},
+    coverage::Coverage,
+    palette::Palette,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TransferMethod {
+    /// Reinhard et al: match the per-channel Lab mean and standard deviation.
+    #[default]
+    MeanStd,
+    /// Pair target and reference palette colors by lightness and shift
+    /// each pixel by the offset of its nearest target color.
+    Palette,
+}
+
+/// Where the colors to transfer come from.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TransferReference {
+    #[default]
+    Image,
+    SavedPalette,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct TransferSettings {
+    pub method: TransferMethod,
+    pub reference: TransferReference,
+    /// 0 keeps the target, 1 is the full transfer.
+    pub strength: f32,
+    /// Colors extracted per image for the palette method.
+    pub palette_size: usize,
+}
+
+impl Default for TransferSettings {
+    fn default() -> Self {
+        Self {
+            method: TransferMethod::default(),
+            reference: TransferReference::default(),
+            strength: 1.,
+            palette_size: 8,
+        }
+    }
+}
+
+/// Weighted Lab colors to take statistics or palettes from.
+pub(crate) struct TransferSource {
+    colors: Vec<(Rgb<u8>, f32)>,
+}
+
+impl TransferSource {
+    pub fn from_image(img: &DynamicImage) -> Self {
+        Self {
+            colors: Palette::extract_from_image(img)
+                .into_iter()
+                .map(|(c, n)| (c, n as f32))
+                .collect(),
+        }
+    }
+
+    /// Palette colors weighted by their coverage of the image they came from,
+    /// or equally when there is none.
+    pub fn from_palette(palette: &Palette) -> Self {
+        let shares = if palette.all_entries.is_empty() {
+            vec![1.; palette.top_rgb.len()]
+        } else {
+            Coverage::palette_shares(&palette.top_rgb, &palette.all_entries)
+        };
+        Self {
+            colors: palette.top_rgb.iter().copied().zip(shares).collect(),
+        }
+    }
+
+    /// Per-channel Lab mean and standard deviation.
+    fn stats(&self) -> ([f32; 3], [f32; 3]) {
+        let total: f32 = self
+            .colors
+            .iter()
+            .map(|(_, w)| w)
+            .sum::<f32>()
+            .max(f32::EPSILON);
+        let labs: Vec<([f32; 3], f32)> = self
+            .colors
+            .iter()
+            .map(|(c, w)| {
+                let lab = Lab::from_rgb(*c);
+                ([lab.l, lab.a, lab.b], *w)
+            })
+            .collect();
+        let mut mean = [0.; 3];
+        for (lab, w) in &labs {
+            for i in 0..3 {
+                mean[i] += lab[i] * w / total;
+            }
+        }
+        let mut var = [0.; 3];
+        for (lab, w) in &labs {
+            for i in 0..3 {
+                var[i] += (lab[i] - mean[i]).powi(2) * w / total;
+            }
+        }
+        (mean, var.map(f32::sqrt))
+    }
+
+    /// Most common distinct colors, sorted dark to light.
+    fn palette(&self, size: usize) -> Vec<Rgb<u8>> {
+        let mut entries: Vec<(Rgb<u8>, usize)> = self
+            .colors
+            .iter()
+            .map(|(c, w)| (*c, (w * 1000.) as usize))
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.1));
+        let (mut colors, _) = Palette::get_top_colors(entries, size);
+        colors.sort_by(|a, b| Lab::from_rgb(*a).l.total_cmp(&Lab::from_rgb(*b).l));
+        colors
+    }
+}
+
+pub(crate) struct ColorTransfer {}
+
+impl ColorTransfer {
+    pub fn transfer(
+        target: &DynamicImage,
+        reference: &TransferSource,
+        settings: &TransferSettings,
+    ) -> RgbaImage {
+        let target_source = TransferSource::from_image(target);
+        let shift: Box<dyn Fn(Lab) -> Lab> = match settings.method {
+            TransferMethod::MeanStd => {
+                let (t_mean, t_std) = target_source.stats();
+                let (r_mean, r_std) = reference.stats();
+                Box::new(move |lab: Lab| {
+                    let v = [lab.l, lab.a, lab.b];
+                    let out: [f32; 3] = std::array::from_fn(|i| {
+                        let scale = if t_std[i] > f32::EPSILON {
+                            r_std[i] / t_std[i]
+                        } else {
+                            1.
+                        };
+                        (v[i] - t_mean[i]) * scale + r_mean[i]
+                    });
+                    Lab {
+                        l: out[0],
+                        a: out[1],
+                        b: out[2],
+                    }
+                })
+            }
+            TransferMethod::Palette => {
+                let offsets = Self::palette_offsets(
+                    &target_source.palette(settings.palette_size),
+                    &reference.palette(settings.palette_size),
+                );
+                let target_labs: Vec<Lab> = offsets.iter().map(|(lab, _)| *lab).collect();
+                Box::new(
+                    move |lab: Lab| match Coverage::nearest_index(&lab, &target_labs) {
+                        Some((i, _)) => {
+                            let offset = offsets[i].1;
+                            Lab {
+                                l: lab.l + offset[0],
+                                a: lab.a + offset[1],
+                                b: lab.b + offset[2],
+                            }
+                        }
+                        None => lab,
+                    },
+                )
+            }
+        };
+
+        let strength = settings.strength.clamp(0., 1.);
+        let mut cache = HashMap::<Rgb<u8>, Rgb<u8>>::new();
+        let mut out = target.to_rgba8();
+        for p in out.pixels_mut() {
+            let c = Rgb([p[0], p[1], p[2]]);
+            let mapped = *cache.entry(c).or_insert_with(|| {
+                let lab = Lab::from_rgb(c);
+                let moved = shift(lab);
+                Lab {
+                    l: lab.l + (moved.l - lab.l) * strength,
+                    a: lab.a + (moved.a - lab.a) * strength,
+                    b: lab.b + (moved.b - lab.b) * strength,
+                }
+                .to_rgb()
+            });
+            *p = Rgba([mapped[0], mapped[1], mapped[2], p[3]]);
+        }
+        out
+    }
+
+    /// Pairs lightness-sorted palettes, which is the optimal transport plan
+    /// along L, spreading the shorter palette over the longer one.
+    fn palette_offsets(target: &[Rgb<u8>], reference: &[Rgb<u8>]) -> Vec<(Lab, [f32; 3])> {
+        if reference.is_empty() {
+            return Vec::new();
+        }
+        target
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let j = if target.len() > 1 {
+                    i * (reference.len() - 1) / (target.len() - 1)
+                } else {
+                    reference.len() / 2
+                };
+                let (t, r) = (Lab::from_rgb(*c), Lab::from_rgb(reference[j]));
+                (t, [r.l - t.l, r.a - t.a, r.b - t.b])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_std_moves_target_to_reference_mean() {
+        let target = DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 8, |x, _| {
+            if x < 4 {
+                Rgba([40, 40, 40, 255])
+            } else {
+                Rgba([90, 90, 90, 255])
+            }
+        }));
+        let reference = DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 8, |x, _| {
+            if x < 4 {
+                Rgba([200, 60, 60, 255])
+            } else {
+                Rgba([230, 90, 90, 255])
+            }
+        }));
+        let out = ColorTransfer::transfer(
+            &target,
+            &TransferSource::from_image(&reference),
+            &TransferSettings::default(),
+        );
+        let (mean, _) = TransferSource::from_image(&DynamicImage::ImageRgba8(out)).stats();
+        let (expected, _) = TransferSource::from_image(&reference).stats();
+        for i in 0..3 {
+            assert!((mean[i] - expected[i]).abs() < 1.5);
+        }
+    }
+}
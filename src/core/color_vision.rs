@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    color::{ColorUtil, Rgb},
+    matrix::{Matrix, mul},
+};
+
+/// Color vision deficiencies, simulated at full severity.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorVision {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+// Machado, Oliveira and Fernandes (2009), severity 1, applied to linear RGB.
+const PROTANOPIA: Matrix = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+const DEUTERANOPIA: Matrix = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+const TRITANOPIA: Matrix = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+impl ColorVision {
+    pub const ALL: [ColorVision; 4] = [
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+        ColorVision::Achromatopsia,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorVision::Protanopia => "Protanopia",
+            ColorVision::Deuteranopia => "Deuteranopia",
+            ColorVision::Tritanopia => "Tritanopia",
+            ColorVision::Achromatopsia => "Achromatopsia",
+        }
+    }
+
+    /// How `c` looks to someone with this deficiency.
+    pub fn simulate(&self, c: Rgb<u8>) -> Rgb<u8> {
+        let linear = c.0.map(ColorUtil::srgb_to_linear);
+        let matrix = match self {
+            ColorVision::Protanopia => PROTANOPIA,
+            ColorVision::Deuteranopia => DEUTERANOPIA,
+            ColorVision::Tritanopia => TRITANOPIA,
+            ColorVision::Achromatopsia => {
+                let y = ColorUtil::relative_luminance(c);
+                return Rgb([ColorUtil::linear_to_srgb(y); 3]);
+            }
+        };
+        Rgb(mul(&matrix, linear).map(ColorUtil::linear_to_srgb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greys_are_unchanged() {
+        for vision in ColorVision::ALL {
+            for v in [0, 128, 255] {
+                let grey = Rgb([v, v, v]);
+                let seen = vision.simulate(grey);
+                assert!(
+                    seen.0.iter().all(|c| c.abs_diff(v) <= 1),
+                    "{vision:?} {seen:?}"
+                );
+            }
+        }
+        // Red and green collapse towards each other without L or M cones
+        let red = ColorVision::Deuteranopia.simulate(Rgb([255, 0, 0]));
+        let green = ColorVision::Deuteranopia.simulate(Rgb([0, 128, 0]));
+        assert!(
+            ColorUtil::color_distance(red, green)
+                < ColorUtil::color_distance(Rgb([255, 0, 0]), Rgb([0, 128, 0])) / 2.
+        );
+    }
+}
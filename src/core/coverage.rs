@@ -0,0 +1,96 @@
+use egui::{Color32, ColorImage};
+use image::RgbaImage;
+use std::collections::HashMap;
+
+use crate::core::color::{HSV, Lab, Rgb};
+
+/// How well a palette represents an image.
+pub(crate) struct Coverage {}
+
+/// ΔE at and above which the heatmap shows full red.
+pub const HEATMAP_MAX_DELTA_E: f32 = 30.;
+
+impl Coverage {
+    /// Index of the palette color closest to `c` by ΔE.
+    pub fn nearest_index(c: &Lab, palette: &[Lab]) -> Option<(usize, f32)> {
+        palette
+            .iter()
+            .map(|p| c.delta_e(p))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Share of the image pixels assigned to each palette color, 0-1.
+    pub fn palette_shares(palette: &[Rgb<u8>], entries: &[(Rgb<u8>, usize)]) -> Vec<f32> {
+        let palette_lab: Vec<Lab> = palette.iter().map(|c| Lab::from_rgb(*c)).collect();
+        let mut counts = vec![0usize; palette.len()];
+        let mut total = 0;
+        for (c, count) in entries {
+            if let Some((i, _)) = Self::nearest_index(&Lab::from_rgb(*c), &palette_lab) {
+                counts[i] += count;
+            }
+            total += count;
+        }
+        counts
+            .into_iter()
+            .map(|c| {
+                if total > 0 {
+                    c as f32 / total as f32
+                } else {
+                    0.
+                }
+            })
+            .collect()
+    }
+
+    /// Per-pixel ΔE to the nearest palette color, blue (close) to red (far).
+    /// Also returns the mean ΔE over the non-transparent pixels.
+    pub fn error_heatmap(img: &RgbaImage, palette: &[Rgb<u8>]) -> (ColorImage, f32) {
+        let palette_lab: Vec<Lab> = palette.iter().map(|c| Lab::from_rgb(*c)).collect();
+        let mut errors = HashMap::<Rgb<u8>, f32>::new();
+        let mut pixels = Vec::with_capacity((img.width() * img.height()) as usize);
+        let mut sum = 0.;
+        let mut total = 0usize;
+        for p in img.pixels() {
+            if p[3] == 0 {
+                pixels.push(Color32::TRANSPARENT);
+                continue;
+            }
+            let c = Rgb([p[0], p[1], p[2]]);
+            let d = *errors.entry(c).or_insert_with(|| {
+                Self::nearest_index(&Lab::from_rgb(c), &palette_lab).map_or(0., |(_, d)| d)
+            });
+            sum += d;
+            total += 1;
+            pixels.push(Self::heat_color(d));
+        }
+        let mean = if total > 0 { sum / total as f32 } else { 0. };
+        (
+            ColorImage::new([img.width() as usize, img.height() as usize], pixels),
+            mean,
+        )
+    }
+
+    fn heat_color(delta_e: f32) -> Color32 {
+        let t = (delta_e / HEATMAP_MAX_DELTA_E).min(1.);
+        let c = HSV::to_rgb(240. * (1. - t), 1., 1.);
+        Color32::from_rgba_unmultiplied(c[0], c[1], c[2], 170)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_follow_nearest_color() {
+        let palette = vec![Rgb([0, 0, 0]), Rgb([255, 255, 255])];
+        let entries = vec![
+            (Rgb([10, 10, 10]), 3),
+            (Rgb([250, 250, 250]), 1),
+            (Rgb([0, 0, 0]), 4),
+        ];
+        let shares = Coverage::palette_shares(&palette, &entries);
+        assert_eq!(shares, vec![0.875, 0.125]);
+    }
+}
@@ -0,0 +1,593 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+use crate::core::{
+    color::{ColorUtil, HSV, Rgb},
+    gamut::{Gamut, WideColor},
+    matrix::{LINEAR_SRGB_TO_XYZ, Matrix, XYZ_TO_LINEAR_SRGB, mul},
+};
+
+/// Bradford chromatic adaptation, CSS `lab()` and `lch()` use a D50 white.
+const D65_TO_D50: Matrix = [
+    [1.047_929_8, 0.022_946_793, -0.050_192_23],
+    [0.029_627_815, 0.990_434_5, -0.017_073_8],
+    [-0.009_243_058, 0.015_055_144, 0.751_874_3],
+];
+const D50_TO_D65: Matrix = [
+    [0.955_576_6, -0.023_039_34, 0.063_163_64],
+    [-0.028_289_54, 1.009_941_6, 0.021_007_7],
+    [0.012_298_16, -0.020_483, 1.329_909_8],
+];
+const D50_WHITE: [f32; 3] = [0.964_295_7, 1., 0.825_104_6];
+const LAB_EPSILON: f32 = 216. / 24389.;
+const LAB_KAPPA: f32 = 24389. / 27.;
+
+const LINEAR_SRGB_TO_LMS: Matrix = [
+    [0.412_221_46, 0.536_332_55, 0.051_445_995],
+    [0.211_903_5, 0.680_699_5, 0.107_396_96],
+    [0.088_302_46, 0.281_718_85, 0.629_978_7],
+];
+const LMS_TO_OKLAB: Matrix = [
+    [0.210_454_26, 0.793_617_8, -0.004_072_047],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_037, 0.782_771_77, -0.808_675_77],
+];
+const OKLAB_TO_LMS: Matrix = [
+    [1., 0.396_337_78, 0.215_803_76],
+    [1., -0.105_561_346, -0.063_854_17],
+    [1., -0.089_484_18, -1.291_485_5],
+];
+const LMS_TO_LINEAR_SRGB: Matrix = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_38],
+    [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+];
+
+/// Text formats a color can be copied as.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorFormat {
+    #[default]
+    Hex,
+    Named,
+    Rgb,
+    Hsl,
+    Hwb,
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+    Color,
+    Vec3,
+    HexInt,
+    Color32,
+}
+
+impl ColorFormat {
+    pub const ALL: [ColorFormat; 13] = [
+        ColorFormat::Hex,
+        ColorFormat::Named,
+        ColorFormat::Rgb,
+        ColorFormat::Hsl,
+        ColorFormat::Hwb,
+        ColorFormat::Lab,
+        ColorFormat::Lch,
+        ColorFormat::Oklab,
+        ColorFormat::Oklch,
+        ColorFormat::Color,
+        ColorFormat::Vec3,
+        ColorFormat::HexInt,
+        ColorFormat::Color32,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorFormat::Hex => "#RRGGBB",
+            ColorFormat::Named => "CSS name",
+            ColorFormat::Rgb => "rgb()",
+            ColorFormat::Hsl => "hsl()",
+            ColorFormat::Hwb => "hwb()",
+            ColorFormat::Lab => "lab()",
+            ColorFormat::Lch => "lch()",
+            ColorFormat::Oklab => "oklab()",
+            ColorFormat::Oklch => "oklch()",
+            ColorFormat::Color => "color(srgb)",
+            ColorFormat::Vec3 => "vec3()",
+            ColorFormat::HexInt => "0xRRGGBB",
+            ColorFormat::Color32 => "Color32::from_rgb",
+        }
+    }
+
+    /// `c` as text, `Named` falls back to hex for colors without a CSS name.
+    pub fn format(&self, c: Rgb<u8>) -> String {
+        let [r, g, b] = c.0;
+        match self {
+            ColorFormat::Hex => ColorUtil::rgb_to_hex(c).to_lowercase(),
+            ColorFormat::Named => CssColor::name_of(c)
+                .map(str::to_string)
+                .unwrap_or_else(|| ColorUtil::rgb_to_hex(c).to_lowercase()),
+            ColorFormat::Rgb => format!("rgb({r} {g} {b})"),
+            ColorFormat::Hsl => {
+                let [h, s, l] = CssColor::to_hsl(c);
+                format!(
+                    "hsl({} {}% {}%)",
+                    num(h, 1),
+                    num(s * 100., 1),
+                    num(l * 100., 1)
+                )
+            }
+            ColorFormat::Hwb => {
+                let hsv = HSV::from_rgb_u(c);
+                let (w, bl) = ((1. - hsv.s) * hsv.v, 1. - hsv.v);
+                format!(
+                    "hwb({} {}% {}%)",
+                    num(hsv.h.rem_euclid(360.), 1),
+                    num(w * 100., 1),
+                    num(bl * 100., 1)
+                )
+            }
+            ColorFormat::Lab => {
+                let [l, a, b] = CssColor::to_lab(c);
+                format!("lab({} {} {})", num(l, 2), num(a, 2), num(b, 2))
+            }
+            ColorFormat::Lch => {
+                let [l, ch, h] = polar(CssColor::to_lab(c));
+                format!("lch({} {} {})", num(l, 2), num(ch, 2), num(h, 1))
+            }
+            ColorFormat::Oklab => {
+                let [l, a, b] = CssColor::to_oklab(c);
+                format!("oklab({} {} {})", num(l, 4), num(a, 4), num(b, 4))
+            }
+            ColorFormat::Oklch => {
+                let [l, ch, h] = polar(CssColor::to_oklab(c));
+                format!("oklch({} {} {})", num(l, 4), num(ch, 4), num(h, 1))
+            }
+            ColorFormat::Color => WideColor::from_srgb(c, Gamut::Srgb).css(),
+            ColorFormat::Vec3 => {
+                let [r, g, b] = c.0.map(|v| num(v as f32 / 255., 4));
+                format!("vec3({r}, {g}, {b})")
+            }
+            ColorFormat::HexInt => format!("0x{r:02X}{g:02X}{b:02X}"),
+            ColorFormat::Color32 => format!("Color32::from_rgb({r}, {g}, {b})"),
+        }
+    }
+}
+
+/// Parses CSS Color Level 4 strings into sRGB.
+pub(crate) struct CssColor {}
+
+impl CssColor {
+    /// Named colors, hex and the `rgb()`, `hsl()`, `hwb()`, `lab()`, `lch()`,
+    /// `oklab()`, `oklch()` and `color()` functions. Alpha is dropped and
+    /// colors outside sRGB are clipped.
+    pub fn parse(text: &str) -> Option<Rgb<u8>> {
+        let text = text.trim().to_ascii_lowercase();
+        if let Some(hex) = text.strip_prefix('#') {
+            return Self::parse_hex(hex);
+        }
+        let Some((name, args)) = text
+            .strip_suffix(')')
+            .and_then(|t| t.split_once('('))
+            .map(|(n, a)| (n.trim(), a))
+        else {
+            return CSS_NAMES
+                .iter()
+                .find(|(n, _)| *n == text)
+                .map(|(_, v)| Rgb([(v >> 16) as u8, (v >> 8) as u8, *v as u8]));
+        };
+        let args: Vec<&str> = args
+            .split([',', ' ', '/', '\t', '\n'])
+            .filter(|a| !a.is_empty())
+            .collect();
+        if args.len() < 3 {
+            return None;
+        }
+
+        let linear = match name {
+            "rgb" | "rgba" => {
+                let c = [0, 1, 2].map(|i| arg(args[i], 255.));
+                return Some(Rgb(
+                    [c[0]?, c[1]?, c[2]?].map(|v| v.clamp(0., 255.).round() as u8)
+                ));
+            }
+            "hsl" | "hsla" => {
+                let (h, s, l) = (hue(args[0])?, arg(args[1], 100.)?, arg(args[2], 100.)?);
+                let (s, l) = ((s / 100.).clamp(0., 1.), (l / 100.).clamp(0., 1.));
+                let v = l + s * l.min(1. - l);
+                let sv = if v > 0. { 2. * (1. - l / v) } else { 0. };
+                return Some(hsv_to_rgb(h, sv, v));
+            }
+            "hwb" => {
+                let (h, w, b) = (hue(args[0])?, arg(args[1], 100.)?, arg(args[2], 100.)?);
+                let (mut w, mut b) = ((w / 100.).clamp(0., 1.), (b / 100.).clamp(0., 1.));
+                if w + b > 1. {
+                    let sum = w + b;
+                    w /= sum;
+                    b /= sum;
+                }
+                let v = 1. - b;
+                let s = if v > 0. { 1. - w / v } else { 0. };
+                return Some(hsv_to_rgb(h, s, v));
+            }
+            "lab" => Self::lab_to_linear([
+                arg(args[0], 100.)?,
+                arg(args[1], 125.)?,
+                arg(args[2], 125.)?,
+            ]),
+            "lch" => Self::lab_to_linear(rectangular([
+                arg(args[0], 100.)?,
+                arg(args[1], 150.)?,
+                hue(args[2])?,
+            ])),
+            "oklab" => {
+                Self::oklab_to_linear([arg(args[0], 1.)?, arg(args[1], 0.4)?, arg(args[2], 0.4)?])
+            }
+            "oklch" => Self::oklab_to_linear(rectangular([
+                arg(args[0], 1.)?,
+                arg(args[1], 0.4)?,
+                hue(args[2])?,
+            ])),
+            "color" if args.len() >= 4 => {
+                let coords = [arg(args[1], 1.)?, arg(args[2], 1.)?, arg(args[3], 1.)?];
+                match args[0] {
+                    "srgb-linear" => coords,
+                    "xyz" | "xyz-d65" => mul(&XYZ_TO_LINEAR_SRGB, coords),
+                    "xyz-d50" => mul(&XYZ_TO_LINEAR_SRGB, mul(&D50_TO_D65, coords)),
+                    space => {
+                        let gamut = Gamut::ALL.into_iter().find(|g| g.css_name() == space)?;
+                        return Some(WideColor { gamut, coords }.to_srgb());
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(Rgb(linear.map(ColorUtil::linear_to_srgb)))
+    }
+
+    fn parse_hex(hex: &str) -> Option<Rgb<u8>> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let width = match hex.len() {
+            3 | 4 => 1,
+            6 | 8 => 2,
+            _ => return None,
+        };
+        let channel = |i: usize| {
+            let v = u8::from_str_radix(&hex[i * width..(i + 1) * width], 16).ok()?;
+            Some(if width == 1 { v * 17 } else { v })
+        };
+        Some(Rgb([channel(0)?, channel(1)?, channel(2)?]))
+    }
+
+    /// CSS keyword for `c`, if it has one.
+    pub fn name_of(c: Rgb<u8>) -> Option<&'static str> {
+        let v = (c[0] as u32) << 16 | (c[1] as u32) << 8 | c[2] as u32;
+        CSS_NAMES.iter().find(|(_, hex)| *hex == v).map(|(n, _)| *n)
+    }
+
+    /// Hue in degrees, saturation and lightness 0..1.
+    fn to_hsl(c: Rgb<u8>) -> [f32; 3] {
+        let hsv = HSV::from_rgb_u(c);
+        let l = hsv.v * (1. - hsv.s / 2.);
+        let s = if l > 0. && l < 1. {
+            (hsv.v - l) / l.min(1. - l)
+        } else {
+            0.
+        };
+        [hsv.h.rem_euclid(360.), s, l]
+    }
+
+    /// CIE Lab relative to D50, as CSS defines it.
+    fn to_lab(c: Rgb<u8>) -> [f32; 3] {
+        let xyz = mul(&D65_TO_D50, mul(&LINEAR_SRGB_TO_XYZ, linear(c)));
+        let f = |i: usize| {
+            let t = xyz[i] / D50_WHITE[i];
+            if t > LAB_EPSILON {
+                t.cbrt()
+            } else {
+                (LAB_KAPPA * t + 16.) / 116.
+            }
+        };
+        let (fx, fy, fz) = (f(0), f(1), f(2));
+        [116. * fy - 16., 500. * (fx - fy), 200. * (fy - fz)]
+    }
+
+    fn lab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+        let fy = (l + 16.) / 116.;
+        let f = [fy + a / 500., fy, fy - b / 200.];
+        let xyz: [f32; 3] = std::array::from_fn(|i| {
+            let t3 = f[i].powi(3);
+            let t = if t3 > LAB_EPSILON {
+                t3
+            } else {
+                (116. * f[i] - 16.) / LAB_KAPPA
+            };
+            t * D50_WHITE[i]
+        });
+        mul(&XYZ_TO_LINEAR_SRGB, mul(&D50_TO_D65, xyz))
+    }
+
+    fn to_oklab(c: Rgb<u8>) -> [f32; 3] {
+        mul(
+            &LMS_TO_OKLAB,
+            mul(&LINEAR_SRGB_TO_LMS, linear(c)).map(f32::cbrt),
+        )
+    }
+
+    fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+        mul(
+            &LMS_TO_LINEAR_SRGB,
+            mul(&OKLAB_TO_LMS, lab).map(|v| v.powi(3)),
+        )
+    }
+}
+
+fn linear(c: Rgb<u8>) -> [f32; 3] {
+    c.0.map(ColorUtil::srgb_to_linear)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Rgb<u8> {
+    let f = HSV::to_rgb_f(h, s, v);
+    Rgb([f[0], f[1], f[2]].map(|c| c.clamp(0., 255.).round() as u8))
+}
+
+/// Lab-like to LCh-like, hue in degrees.
+fn polar([l, a, b]: [f32; 3]) -> [f32; 3] {
+    [l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.)]
+}
+
+fn rectangular([l, c, h]: [f32; 3]) -> [f32; 3] {
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+/// Number or percentage, where 100% is `full`. `none` is zero.
+fn arg(a: &str, full: f32) -> Option<f32> {
+    if a == "none" {
+        return Some(0.);
+    }
+    match a.strip_suffix('%') {
+        Some(p) => Some(p.parse::<f32>().ok()? / 100. * full),
+        None => a.parse().ok(),
+    }
+}
+
+/// Angle in degrees from a number or `deg`, `rad`, `grad` or `turn`.
+fn hue(a: &str) -> Option<f32> {
+    if a == "none" {
+        return Some(0.);
+    }
+    let (v, scale) = [
+        ("deg", 1.),
+        ("grad", 0.9),
+        ("rad", 180. / PI),
+        ("turn", 360.),
+    ]
+    .iter()
+    .find_map(|(unit, scale)| a.strip_suffix(unit).map(|v| (v, *scale)))
+    .unwrap_or((a, 1.));
+    Some((v.parse::<f32>().ok()? * scale).rem_euclid(360.))
+}
+
+/// Fixed decimals without trailing zeros.
+fn num(v: f32, decimals: usize) -> String {
+    let s = format!("{v:.decimals$}");
+    let s = if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &s
+    };
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// CSS named colors, aliases after the name they share a value with.
+const CSS_NAMES: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_css_color_4() {
+        let orange = Rgb([255, 165, 0]);
+        for text in [
+            "orange",
+            "#FFA500",
+            "#ffa500ff",
+            "rgb(255, 165, 0)",
+            "rgb(100% 64.7% 0% / 0.5)",
+            "hsl(38.8 100% 50%)",
+            "hwb(0.1078turn 0% 0%)",
+            "lab(75.59 27.52 79.12)",
+            "lch(75.59 83.77 70.82)",
+            "oklab(0.7927 0.0566 0.1614)",
+            "oklch(0.7927 0.1711 70.67)",
+            "color(srgb 1 0.647 0)",
+        ] {
+            let parsed = CssColor::parse(text);
+            let close =
+                parsed.is_some_and(|c| c.0.iter().zip(orange.0).all(|(a, b)| a.abs_diff(b) <= 1));
+            assert!(close, "{text} -> {parsed:?}");
+        }
+        assert_eq!(CssColor::parse("#abc"), Some(Rgb([170, 187, 204])));
+        assert_eq!(CssColor::parse("notacolor"), None);
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let c = Rgb([121, 131, 136]);
+        // Code formats are not CSS, every other format must parse back
+        let code = [ColorFormat::Vec3, ColorFormat::HexInt, ColorFormat::Color32];
+        for format in ColorFormat::ALL.into_iter().filter(|f| !code.contains(f)) {
+            let text = format.format(c);
+            let parsed = CssColor::parse(&text);
+            assert!(parsed.is_some(), "{text} does not parse");
+            assert!(
+                parsed.is_some_and(|p| p.0.iter().zip(c.0).all(|(a, b)| a.abs_diff(b) <= 1)),
+                "{text} -> {parsed:?}"
+            );
+        }
+        assert_eq!(
+            ColorFormat::Named.format(Rgb([102, 51, 153])),
+            "rebeccapurple"
+        );
+        assert_eq!(ColorFormat::HexInt.format(c), "0x798388");
+        assert_eq!(
+            ColorFormat::Color32.format(c),
+            "Color32::from_rgb(121, 131, 136)"
+        );
+    }
+}
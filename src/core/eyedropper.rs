@@ -0,0 +1,131 @@
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::core::color::Rgb;
+
+/// Area averaged by the eyedropper around the picked pixel.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SampleSize {
+    #[default]
+    Point,
+    Three,
+    Five,
+}
+
+impl SampleSize {
+    pub fn radius(&self) -> u32 {
+        match self {
+            SampleSize::Point => 0,
+            SampleSize::Three => 1,
+            SampleSize::Five => 2,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SampleSize::Point => "1x1",
+            SampleSize::Three => "3x3",
+            SampleSize::Five => "5x5",
+        }
+    }
+}
+
+pub(crate) struct Eyedropper {}
+
+impl Eyedropper {
+    /// Maps a screen position inside `rect`, where the whole image is drawn,
+    /// to the source pixel under it.
+    pub fn image_pixel(
+        rect: egui::Rect,
+        pos: egui::Pos2,
+        img_size: (u32, u32),
+    ) -> Option<(u32, u32)> {
+        if !rect.contains(pos) || rect.width() <= 0. || rect.height() <= 0. {
+            return None;
+        }
+        let u = (pos.x - rect.min.x) / rect.width();
+        let v = (pos.y - rect.min.y) / rect.height();
+        let x = ((u * img_size.0 as f32) as u32).min(img_size.0.saturating_sub(1));
+        let y = ((v * img_size.1 as f32) as u32).min(img_size.1.saturating_sub(1));
+        Some((x, y))
+    }
+
+    /// Average color of the pixels around (x, y), clipped to the image.
+    /// Fully transparent pixels are skipped.
+    pub fn sample(img: &DynamicImage, x: u32, y: u32, size: SampleSize) -> Option<Rgb<u8>> {
+        let r = size.radius();
+        let (width, height) = img.dimensions();
+        let mut sum = [0u32; 3];
+        let mut n = 0;
+        for py in y.saturating_sub(r)..=(y + r).min(height.saturating_sub(1)) {
+            for px in x.saturating_sub(r)..=(x + r).min(width.saturating_sub(1)) {
+                let p = img.get_pixel(px, py);
+                if p[3] == 0 {
+                    continue;
+                }
+                sum[0] += p[0] as u32;
+                sum[1] += p[1] as u32;
+                sum[2] += p[2] as u32;
+                n += 1;
+            }
+        }
+        if n == 0 {
+            return None;
+        }
+        Some(Rgb([
+            (sum[0] / n) as u8,
+            (sum[1] / n) as u8,
+            (sum[2] / n) as u8,
+        ]))
+    }
+
+    /// Source pixels around (x, y) for the loupe, `None` outside the image.
+    pub fn neighborhood(
+        img: &DynamicImage,
+        x: u32,
+        y: u32,
+        half: i32,
+    ) -> Vec<Vec<Option<egui::Color32>>> {
+        let (width, height) = img.dimensions();
+        (-half..=half)
+            .map(|dy| {
+                (-half..=half)
+                    .map(|dx| {
+                        let px = x as i32 + dx;
+                        let py = y as i32 + dy;
+                        if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+                            return None;
+                        }
+                        let p = img.get_pixel(px as u32, py as u32);
+                        Some(egui::Color32::from_rgba_unmultiplied(
+                            p[0], p[1], p[2], p[3],
+                        ))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_screen_to_source_pixels() {
+        let rect = egui::Rect::from_min_size(egui::pos2(100., 50.), egui::vec2(200., 100.));
+        let size = (20, 10);
+        assert_eq!(
+            Eyedropper::image_pixel(rect, egui::pos2(100., 50.), size),
+            Some((0, 0))
+        );
+        assert_eq!(
+            Eyedropper::image_pixel(rect, egui::pos2(300., 150.), size),
+            Some((19, 9))
+        );
+        assert_eq!(
+            Eyedropper::image_pixel(rect, egui::pos2(99., 50.), size),
+            None
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::color::{ColorUtil, Rgb};
+use crate::core::matrix::{Matrix, mul};
+
+const SRGB_TO_P3: Matrix = [
+    [0.822_462_1, 0.177_538, 0.],
+    [0.033_194_1, 0.966_805_8, 0.],
+    [0.017_082_7, 0.072_397_4, 0.910_519_9],
+];
+const P3_TO_SRGB: Matrix = [
+    [1.224_940_1, -0.224_940_4, 0.],
+    [-0.042_056_9, 1.042_057_1, 0.],
+    [-0.019_637_6, -0.078_636_1, 1.098_273_5],
+];
+const SRGB_TO_REC2020: Matrix = [
+    [0.627_403_9, 0.329_283, 0.043_313_1],
+    [0.069_097_3, 0.919_540_4, 0.011_362_3],
+    [0.016_391_4, 0.088_013_3, 0.895_595_3],
+];
+const REC2020_TO_SRGB: Matrix = [
+    [1.660_491, -0.587_641_1, -0.072_849_9],
+    [-0.124_550_5, 1.132_899_9, -0.008_349_4],
+    [-0.018_150_8, -0.100_578_9, 1.118_729_7],
+];
+
+/// BT.2020 transfer constants, as used by CSS `color(rec2020 …)`.
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_97;
+
+/// Channel values this far outside 0..1 still count as in gamut, to absorb rounding.
+const GAMUT_EPSILON: f32 = 0.001;
+
+/// RGB color spaces colors can be picked in and exported to.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Gamut {
+    #[default]
+    Srgb,
+    DisplayP3,
+    Rec2020,
+}
+
+impl Gamut {
+    pub const ALL: [Gamut; 3] = [Gamut::Srgb, Gamut::DisplayP3, Gamut::Rec2020];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Gamut::Srgb => "sRGB",
+            Gamut::DisplayP3 => "Display P3",
+            Gamut::Rec2020 => "Rec.2020",
+        }
+    }
+
+    /// Identifier used by CSS `color()` and design tokens.
+    pub fn css_name(&self) -> &'static str {
+        match self {
+            Gamut::Srgb => "srgb",
+            Gamut::DisplayP3 => "display-p3",
+            Gamut::Rec2020 => "rec2020",
+        }
+    }
+
+    fn matrix_from_srgb(self) -> Option<&'static Matrix> {
+        match self {
+            Gamut::Srgb => None,
+            Gamut::DisplayP3 => Some(&SRGB_TO_P3),
+            Gamut::Rec2020 => Some(&SRGB_TO_REC2020),
+        }
+    }
+
+    fn matrix_to_srgb(self) -> Option<&'static Matrix> {
+        match self {
+            Gamut::Srgb => None,
+            Gamut::DisplayP3 => Some(&P3_TO_SRGB),
+            Gamut::Rec2020 => Some(&REC2020_TO_SRGB),
+        }
+    }
+
+    /// Transfer curves are extended to negative values by symmetry, so
+    /// out-of-gamut colors survive the round trip.
+    fn decode(&self, v: f32) -> f32 {
+        let sign = v.signum();
+        let v = v.abs();
+        let linear = match self {
+            Gamut::Srgb | Gamut::DisplayP3 => {
+                if v <= 0.04045 {
+                    v / 12.92
+                } else {
+                    ((v + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            Gamut::Rec2020 => {
+                if v < REC2020_BETA * 4.5 {
+                    v / 4.5
+                } else {
+                    ((v + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45)
+                }
+            }
+        };
+        sign * linear
+    }
+
+    fn encode(&self, v: f32) -> f32 {
+        let sign = v.signum();
+        let v = v.abs();
+        let encoded = match self {
+            Gamut::Srgb | Gamut::DisplayP3 => {
+                if v <= 0.003_130_8 {
+                    v * 12.92
+                } else {
+                    1.055 * v.powf(1. / 2.4) - 0.055
+                }
+            }
+            Gamut::Rec2020 => {
+                if v < REC2020_BETA {
+                    v * 4.5
+                } else {
+                    REC2020_ALPHA * v.powf(0.45) - (REC2020_ALPHA - 1.)
+                }
+            }
+        };
+        sign * encoded
+    }
+}
+
+/// A color as gamma encoded 0..1 coordinates in some `Gamut`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WideColor {
+    pub gamut: Gamut,
+    pub coords: [f32; 3],
+}
+
+impl WideColor {
+    pub fn from_srgb(c: Rgb<u8>, gamut: Gamut) -> Self {
+        Self::from_srgb_coords(c.0.map(|v| v as f32 / 255.), gamut)
+    }
+
+    fn from_srgb_coords(srgb: [f32; 3], gamut: Gamut) -> Self {
+        let linear = srgb.map(|v| Gamut::Srgb.decode(v));
+        let linear = match gamut.matrix_from_srgb() {
+            Some(m) => mul(m, linear),
+            None => linear,
+        };
+        Self {
+            gamut,
+            coords: linear.map(|v| gamut.encode(v)),
+        }
+    }
+
+    /// Encoded sRGB coordinates, not clamped, so they can fall outside 0..1.
+    pub fn srgb_coords(&self) -> [f32; 3] {
+        let linear = self.coords.map(|v| self.gamut.decode(v));
+        let linear = match self.gamut.matrix_to_srgb() {
+            Some(m) => mul(m, linear),
+            None => linear,
+        };
+        linear.map(|v| Gamut::Srgb.encode(v))
+    }
+
+    pub fn in_srgb(&self) -> bool {
+        self.srgb_coords()
+            .iter()
+            .all(|v| (-GAMUT_EPSILON..=1. + GAMUT_EPSILON).contains(v))
+    }
+
+    /// Nearest displayable sRGB color, clipped per channel.
+    pub fn to_srgb(self) -> Rgb<u8> {
+        Rgb(self
+            .srgb_coords()
+            .map(|v| (v.clamp(0., 1.) * 255.).round() as u8))
+    }
+
+    /// Same color expressed in another gamut, possibly outside 0..1 there.
+    pub fn convert(&self, gamut: Gamut) -> Self {
+        Self::from_srgb_coords(self.srgb_coords(), gamut)
+    }
+
+    /// CSS Color 4 `color()` function, e.g. `color(display-p3 1 0 0)`.
+    pub fn css(&self) -> String {
+        let [r, g, b] = self.coords.map(Self::format_component);
+        format!("color({} {r} {g} {b})", self.gamut.css_name())
+    }
+
+    /// Design token value in the DTCG color format, with an sRGB hex fallback.
+    pub fn token_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "colorSpace": self.gamut.css_name(),
+            "components": self.coords.map(|v| (v * 10000.).round() / 10000.),
+            "hex": ColorUtil::rgb_to_hex(self.to_srgb()),
+        })
+    }
+
+    fn format_component(v: f32) -> String {
+        let s = format!("{:.4}", v);
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+        if s == "-0" {
+            "0".to_string()
+        } else {
+            s.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_round_trips_through_wide_gamuts() {
+        let c = Rgb([200, 30, 90]);
+        for gamut in Gamut::ALL {
+            let wide = WideColor::from_srgb(c, gamut);
+            assert!(wide.in_srgb());
+            assert_eq!(wide.to_srgb(), c);
+        }
+    }
+
+    #[test]
+    fn p3_red_is_flagged_outside_srgb() {
+        let red = WideColor {
+            gamut: Gamut::DisplayP3,
+            coords: [1., 0., 0.],
+        };
+        assert!(!red.in_srgb());
+        let back = red.convert(Gamut::Rec2020).convert(Gamut::DisplayP3);
+        assert!((0..3).all(|i| (back.coords[i] - red.coords[i]).abs() < 0.001));
+        assert_eq!(red.to_srgb(), Rgb([255, 0, 0]));
+        assert_eq!(red.css(), "color(display-p3 1 0 0)");
+    }
+}
@@ -0,0 +1,94 @@
+use egui::{Color32, ColorImage};
+use image::RgbaImage;
+use std::collections::HashMap;
+
+use crate::core::color::{Lab, Rgb};
+
+/// Mask over a source image marking the pixels within `delta_e` of `color`.
+pub(crate) struct Highlight {
+    pub color: Rgb<u8>,
+    pub delta_e: f32,
+    /// Share of the non-transparent pixels that matched, 0-1.
+    pub coverage: f32,
+    /// Transparent where pixels match, dark elsewhere. Drawn over the image.
+    pub mask: ColorImage,
+}
+
+const DIM_COLOR: Color32 = Color32::from_black_alpha(200);
+
+impl Highlight {
+    pub fn new(img: &RgbaImage, color: Rgb<u8>, delta_e: f32) -> Self {
+        let target = Lab::from_rgb(color);
+        // Images usually have far fewer unique colors than pixels
+        let mut matches = HashMap::<Rgb<u8>, bool>::new();
+        let mut pixels = Vec::with_capacity((img.width() * img.height()) as usize);
+        let mut total = 0usize;
+        let mut matched = 0usize;
+        for p in img.pixels() {
+            if p[3] == 0 {
+                pixels.push(Color32::TRANSPARENT);
+                continue;
+            }
+            let c = Rgb([p[0], p[1], p[2]]);
+            let is_match = *matches
+                .entry(c)
+                .or_insert_with(|| Lab::from_rgb(c).delta_e(&target) <= delta_e);
+            total += 1;
+            if is_match {
+                matched += 1;
+                pixels.push(Color32::TRANSPARENT);
+            } else {
+                pixels.push(DIM_COLOR);
+            }
+        }
+        let coverage = if total > 0 {
+            matched as f32 / total as f32
+        } else {
+            0.
+        };
+        Self {
+            color,
+            delta_e,
+            coverage,
+            mask: ColorImage::new([img.width() as usize, img.height() as usize], pixels),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_pixels_within_delta_e() {
+        let img = RgbaImage::from_vec(
+            4,
+            1,
+            [
+                [255, 0, 0, 255],
+                [250, 5, 5, 255],
+                [0, 0, 255, 255],
+                [255, 0, 0, 0],
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        let highlight = Highlight::new(&img, Rgb([255, 0, 0]), 10.);
+        assert_eq!(
+            highlight.mask.pixels,
+            vec![
+                Color32::TRANSPARENT,
+                Color32::TRANSPARENT,
+                DIM_COLOR,
+                Color32::TRANSPARENT,
+            ]
+        );
+        // The transparent pixel doesn't count towards coverage
+        assert!((highlight.coverage - 2. / 3.).abs() < 1e-6);
+
+        let exact = Highlight::new(&img, Rgb([255, 0, 0]), 0.);
+        assert_eq!(exact.mask.pixels[1], DIM_COLOR);
+        assert!((exact.coverage - 1. / 3.).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,189 @@
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+
+use crate::core::color::{ColorUtil, Rgb};
+
+/// Lattice sizes offered for `.cube` files.
+pub const CUBE_SIZES: [usize; 3] = [17, 33, 65];
+
+/// 2×2×2 ordered dither thresholds, indexed by the low bit of each lattice axis.
+const BAYER_2X2X2: [f32; 8] = [0., 4., 6., 2., 7., 3., 1., 5.];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextureOrientation {
+    /// N×1, one column per color.
+    #[default]
+    Row,
+    /// 1×N, one row per color.
+    Column,
+}
+
+/// How a `.cube` lattice point becomes a palette color.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LutMapping {
+    #[default]
+    Nearest,
+    /// Points between two palette colors alternate between them in an ordered
+    /// pattern, so interpolating shaders get a mix instead of a hard edge.
+    Dither,
+    /// Blends towards the second nearest color close to the boundary between
+    /// them. Output is no longer limited to palette colors.
+    Smooth,
+}
+
+impl LutMapping {
+    pub const ALL: [LutMapping; 3] = [LutMapping::Nearest, LutMapping::Dither, LutMapping::Smooth];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LutMapping::Nearest => "Nearest",
+            LutMapping::Dither => "Dithered",
+            LutMapping::Smooth => "Smoothed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct LutSettings {
+    pub orientation: TextureOrientation,
+    pub cube_size: usize,
+    pub mapping: LutMapping,
+}
+
+impl Default for LutSettings {
+    fn default() -> Self {
+        Self {
+            orientation: TextureOrientation::default(),
+            cube_size: 33,
+            mapping: LutMapping::default(),
+        }
+    }
+}
+
+pub(crate) struct Lut {}
+
+impl Lut {
+    /// Palette colors in order as a one pixel high or wide strip, for
+    /// sampling by index in a shader.
+    pub fn lookup_texture(colors: &[Rgb<u8>], orientation: TextureOrientation) -> RgbImage {
+        let n = colors.len().max(1) as u32;
+        let (width, height) = match orientation {
+            TextureOrientation::Row => (n, 1),
+            TextureOrientation::Column => (1, n),
+        };
+        let mut img = RgbImage::new(width, height);
+        for (i, c) in colors.iter().enumerate() {
+            let (x, y) = match orientation {
+                TextureOrientation::Row => (i as u32, 0),
+                TextureOrientation::Column => (0, i as u32),
+            };
+            img.put_pixel(x, y, image::Rgb(c.0));
+        }
+        img
+    }
+
+    /// Adobe/Resolve `.cube` 3D LUT mapping every input to the palette.
+    pub fn cube(colors: &[Rgb<u8>], settings: &LutSettings, title: &str) -> String {
+        let n = settings.cube_size.max(2);
+        let mut out = format!(
+            "TITLE \"{}\"\nLUT_3D_SIZE {n}\nDOMAIN_MIN 0.0 0.0 0.0\nDOMAIN_MAX 1.0 1.0 1.0\n",
+            title.replace('"', "'")
+        );
+        let level = |i: usize| (i as f32 * 255. / (n - 1) as f32).round() as u8;
+        // Red changes fastest
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n {
+                    let input = Rgb([level(r), level(g), level(b)]);
+                    let threshold = (BAYER_2X2X2[(r & 1) << 2 | (g & 1) << 1 | (b & 1)] + 0.5) / 8.;
+                    let [or, og, ob] = Self::map(input, colors, settings.mapping, threshold);
+                    out.push_str(&format!("{or:.6} {og:.6} {ob:.6}\n"));
+                }
+            }
+        }
+        out
+    }
+
+    /// Output for one lattice point as 0-1 channels.
+    fn map(c: Rgb<u8>, colors: &[Rgb<u8>], mapping: LutMapping, threshold: f32) -> [f32; 3] {
+        let to_f = |c: Rgb<u8>| c.0.map(|v| v as f32 / 255.);
+        let mut nearest: Option<(f32, Rgb<u8>)> = None;
+        let mut second: Option<(f32, Rgb<u8>)> = None;
+        for p in colors {
+            let d = ColorUtil::color_distance(c, *p);
+            if nearest.is_none_or(|(nd, _)| d < nd) {
+                second = nearest;
+                nearest = Some((d, *p));
+            } else if second.is_none_or(|(sd, _)| d < sd) {
+                second = Some((d, *p));
+            }
+        }
+        let Some((d1, c1)) = nearest else {
+            return to_f(c);
+        };
+        let Some((d2, c2)) = second.filter(|(d2, _)| *d2 > 0.) else {
+            return to_f(c1);
+        };
+        match mapping {
+            LutMapping::Nearest => to_f(c1),
+            LutMapping::Dither => {
+                // 0 on top of the nearest color, 0.5 halfway to the second
+                let share = d1 / (d1 + d2);
+                if threshold < share {
+                    to_f(c2)
+                } else {
+                    to_f(c1)
+                }
+            }
+            LutMapping::Smooth => {
+                let w2 = (d1 / d2).powi(4);
+                let l1 = c1.0.map(ColorUtil::srgb_to_linear);
+                let l2 = c2.0.map(ColorUtil::srgb_to_linear);
+                let mixed: [f32; 3] = std::array::from_fn(|i| (l1[i] + l2[i] * w2) / (1. + w2));
+                mixed.map(|v| ColorUtil::linear_to_srgb(v) as f32 / 255.)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_and_cube() {
+        let colors = [Rgb([0, 0, 0]), Rgb([255, 0, 0]), Rgb([255, 255, 255])];
+        let texture = Lut::lookup_texture(&colors, TextureOrientation::Column);
+        assert_eq!(texture.dimensions(), (1, 3));
+        assert_eq!(texture.get_pixel(0, 1), &image::Rgb([255, 0, 0]));
+
+        let settings = LutSettings {
+            cube_size: 17,
+            ..LutSettings::default()
+        };
+        let cube = Lut::cube(&colors, &settings, "Test");
+        let mut lines = cube.lines();
+        assert_eq!(lines.nth(1), Some("LUT_3D_SIZE 17"));
+        let values: Vec<&str> = lines.skip(2).collect();
+        assert_eq!(values.len(), 17 * 17 * 17);
+        assert_eq!(values[0], "0.000000 0.000000 0.000000");
+        assert_eq!(values[16], "1.000000 0.000000 0.000000");
+        assert_eq!(values[values.len() - 1], "1.000000 1.000000 1.000000");
+        let outputs = [values[0], values[16], values[values.len() - 1]];
+        assert!(values.iter().all(|v| outputs.contains(v)));
+
+        for mapping in [LutMapping::Dither, LutMapping::Smooth] {
+            let settings = LutSettings {
+                cube_size: 17,
+                mapping,
+                ..LutSettings::default()
+            };
+            let cube = Lut::cube(&colors, &settings, "Test");
+            assert_eq!(cube.lines().count(), 4 + 17 * 17 * 17);
+            let mapped: Vec<&str> = cube.lines().skip(4).collect();
+            assert_ne!(mapped, values);
+            // Palette colors always map to themselves
+            assert_eq!(mapped[16], "1.000000 0.000000 0.000000");
+        }
+    }
+}
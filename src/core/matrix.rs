@@ -0,0 +1,28 @@
+/// Row-major 3×3 matrix converting between linear color spaces.
+pub(crate) type Matrix = [[f32; 3]; 3];
+
+/// Linear sRGB to CIE XYZ, both relative to D65.
+pub(crate) const LINEAR_SRGB_TO_XYZ: Matrix = [
+    [0.412_390_8, 0.357_584_3, 0.180_480_8],
+    [0.212_639, 0.715_168_7, 0.072_192_32],
+    [0.019_330_82, 0.119_194_78, 0.950_532_1],
+];
+pub(crate) const XYZ_TO_LINEAR_SRGB: Matrix = [
+    [3.240_97, -1.537_383_2, -0.498_610_76],
+    [-0.969_243_6, 1.875_967_5, 0.041_555_06],
+    [0.055_630_08, -0.203_976_96, 1.056_971_5],
+];
+
+pub(crate) fn mul(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+pub(crate) fn mul_matrix(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [[0.; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
@@ -1,6 +1,25 @@
+pub mod adjustments;
 pub mod color;
 pub mod color_detail;
 pub mod color_names;
 pub mod color_relation;
+pub mod color_space;
+pub mod color_transfer;
+pub mod color_vision;
+pub mod coverage;
+pub mod css_color;
+pub mod eyedropper;
+pub mod gamut;
+pub mod highlight;
+pub mod lut;
+pub mod matrix;
 pub mod palette;
+pub mod palette_map;
+pub mod path_sampler;
+pub mod pixelate;
+pub mod saliency;
+pub mod segmentation;
 pub mod similar;
+pub mod style_guide;
+pub mod swatch_sheet;
+pub mod terminal_theme;
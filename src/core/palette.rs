@@ -1,7 +1,6 @@
-use image::ImageReader;
+use image::DynamicImage;
 use rand::rng;
 use rand::seq::SliceRandom; // For shuffling the array
-use raqote::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{self, Write};
@@ -10,6 +9,48 @@ use std::thread;
 use uuid::Uuid;
 
 use crate::core::color::{ColorUtil, Rgb};
+use crate::core::color_names::ColorNames;
+use crate::core::color_space::ColorManagement;
+use crate::core::saliency::{Saliency, WeightGrid};
+use crate::core::terminal_theme::TerminalTheme;
+
+mod aco;
+mod act;
+mod ase;
+mod bytes;
+mod gpl;
+mod jasc;
+mod kpl;
+mod paint_net;
+mod snippets;
+mod text;
+mod tokens;
+
+pub(crate) use ase::SwatchType;
+pub(crate) use snippets::{CodeTemplate, TEMPLATE_DIR};
+pub(crate) use text::TEXT_EXTENSIONS;
+pub(crate) use tokens::{ROLES, StyleFormat, TokenNaming, TokenSettings};
+
+/// A palette file format that can be imported and exported.
+pub(crate) trait PaletteFormat: Sync {
+    /// Shown in the file dialogs.
+    fn name(&self) -> &'static str;
+    /// Lower case, without the dot.
+    fn extensions(&self) -> &'static [&'static str];
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette>;
+    fn write(&self, palette: &Palette, palette_name: &str) -> io::Result<Vec<u8>>;
+}
+
+/// Every importable and exportable palette format, GPL first as the default.
+pub(crate) static PALETTE_FORMATS: &[&dyn PaletteFormat] = &[
+    &gpl::Gpl,
+    &ase::Ase,
+    &aco::Aco,
+    &jasc::JascPal,
+    &act::Act,
+    &paint_net::PaintNet,
+    &kpl::Kpl,
+];
 
 #[derive(Debug, Clone)]
 // #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,6 +63,21 @@ pub(crate) struct Palette {
     pub current_path: Option<String>,
     pub all_entries: Vec<(Rgb<u8>, usize)>,
     pub palette_size: usize,
+    /// Names that came with imported swatches, by color.
+    #[serde(default)]
+    pub color_names: Vec<(Rgb<u8>, String)>,
+    /// Design roles such as "primary", used to name exported tokens.
+    #[serde(default)]
+    pub roles: Vec<(Rgb<u8>, String)>,
+    /// Spot and global swatch types from imported ASE files, process is implied.
+    #[serde(default)]
+    pub swatch_types: Vec<(Rgb<u8>, SwatchType)>,
+    /// ASE group each color was imported in, ungrouped colors are left out.
+    #[serde(default)]
+    pub swatch_groups: Vec<(Rgb<u8>, String)>,
+    /// ANSI and editor slot assignments, `None` until first suggested.
+    #[serde(default)]
+    pub terminal_theme: Option<TerminalTheme>,
 }
 
 impl Default for Palette {
@@ -34,6 +90,11 @@ impl Default for Palette {
             current_path: None,
             all_entries: Vec::new(),
             palette_size: 16,
+            color_names: Vec::new(),
+            roles: Vec::new(),
+            swatch_types: Vec::new(),
+            swatch_groups: Vec::new(),
+            terminal_theme: None,
         }
     }
 }
@@ -59,7 +120,85 @@ impl Palette {
             top_hex,
             all_entries,
             current_path: None,
+            color_names: Vec::new(),
+            roles: Vec::new(),
+            swatch_types: Vec::new(),
+            swatch_groups: Vec::new(),
+            terminal_theme: None,
+        }
+    }
+
+    /// Palette of exactly `colors`, keeping any names they come with.
+    pub fn from_colors(palette_name: &str, colors: Vec<(Rgb<u8>, Option<String>)>) -> Palette {
+        let mut palette = Palette {
+            palette_name: palette_name.to_string(),
+            ..Default::default()
+        };
+        for (c, name) in colors {
+            palette.top_rgb.push(c);
+            palette.top_hex.push(ColorUtil::rgb_to_hex(c));
+            palette.all_entries.push((c, 1));
+            if let Some(name) = name.filter(|n| !n.is_empty()) {
+                palette.color_names.push((c, name));
+            }
         }
+        palette.palette_size = palette.top_rgb.len();
+        palette
+    }
+
+    /// Imported name of `c`, else the closest name from `ColorNames`, else its hex.
+    pub fn color_name(&self, c: &Rgb<u8>) -> String {
+        if let Some((_, name)) = self.color_names.iter().find(|(named, _)| named == c) {
+            return name.clone();
+        }
+        ColorNames::nearest_color_name(c).unwrap_or_else(|| ColorUtil::rgb_to_hex(*c))
+    }
+
+    /// Format matching the extension of `path`, if it's a palette file.
+    pub fn format_for_path(path: &str) -> Option<&'static dyn PaletteFormat> {
+        let ext = std::path::Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_lowercase();
+        PALETTE_FORMATS
+            .iter()
+            .copied()
+            .find(|f| f.extensions().contains(&ext.as_str()))
+    }
+
+    pub fn is_palette_file(path: &str) -> bool {
+        Self::format_for_path(path).is_some() || Self::is_text_file(path)
+    }
+
+    pub fn load_file(path: &str) -> io::Result<Palette> {
+        let bytes = std::fs::read(path)?;
+        let scan_text = || Self::from_text(&String::from_utf8_lossy(&bytes));
+        let mut palette = match Self::format_for_path(path) {
+            Some(format) => format.read(&bytes).or_else(|e| {
+                if Self::is_text_file(path) {
+                    scan_text()
+                } else {
+                    Err(e)
+                }
+            })?,
+            None if Self::is_text_file(path) => scan_text()?,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unknown palette format",
+                ));
+            }
+        };
+        if palette.palette_name.is_empty() {
+            palette.palette_name = Self::get_file_name(path);
+        }
+        Ok(palette)
+    }
+
+    /// Writes in the format matching the extension, GPL when there is none.
+    pub fn save_file(&self, path: &str) -> io::Result<()> {
+        let format = Self::format_for_path(path).unwrap_or(&gpl::Gpl);
+        std::fs::write(path, format.write(self, &Self::get_file_name(path))?)
     }
 
     pub fn update(&mut self, path: &str) {
@@ -87,6 +226,27 @@ impl Palette {
         self.update_top_colors();
     }
 
+    /// Re-extracts from an already decoded, possibly adjusted, image of `path`.
+    pub fn update_from_image(&mut self, path: &str, img: &DynamicImage) {
+        self.update_from_counts(path, Self::extract_from_image(img));
+    }
+
+    /// Like `update_from_image`, but ranks colors by saliency weighted counts
+    /// so small, striking areas can make it into the top colors.
+    pub fn update_weighted(&mut self, path: &str, img: &DynamicImage, weights: &WeightGrid) {
+        self.update_from_counts(path, Saliency::weighted_histogram(img, weights));
+    }
+
+    fn update_from_counts(&mut self, path: &str, counts: HashMap<Rgb<u8>, usize>) {
+        self.current_path = Some(path.to_string());
+        self.palette_name = Self::get_file_name(path);
+        self.all_entries = Self::get_sorted_entries(counts);
+        if self.all_entries.len() < self.palette_size {
+            self.palette_size = self.all_entries.len()
+        }
+        self.update_top_colors();
+    }
+
     pub fn update_top_colors(&mut self) {
         let res = Self::get_top_colors(self.all_entries.clone(), self.palette_size);
         self.top_rgb = res.0;
@@ -146,10 +306,6 @@ impl Palette {
         }
     }
 
-    pub fn save_palette_img(&mut self, palette_name: String) {
-        self.output_palette(&palette_name);
-    }
-
     pub fn save_palette_text(&mut self, palette_name: String) {
         if let Err(e) = self.output_palette_txt(&palette_name) {
             eprintln!("Error writing to file: {}", e);
@@ -207,6 +363,19 @@ impl Palette {
         }
     }
 
+    /// Adds the colors that aren't already close to one in the palette.
+    pub fn merge_colors(&mut self, colors: &[Rgb<u8>]) {
+        for c in colors {
+            if self
+                .top_rgb
+                .iter()
+                .all(|t| ColorUtil::color_distance(*c, *t) >= 20.)
+            {
+                self.add_new_color(*c);
+            }
+        }
+    }
+
     pub fn add_new_color(&mut self, c: Rgb<u8>) {
         self.palette_size += 1;
         self.top_rgb.push(c);
@@ -215,7 +384,15 @@ impl Palette {
 
     pub fn extract_pallete(path: &str) -> Option<HashMap<Rgb<u8>, usize>> {
         println!("Extracting palette from {path} ");
-        let img = ImageReader::open(path).unwrap().decode().unwrap();
+        let (img, _) = ColorManagement::open(path).unwrap();
+        let pix = Self::extract_from_image(&img);
+
+        println!("palette extracted");
+
+        Some(pix)
+    }
+
+    pub fn extract_from_image(img: &DynamicImage) -> HashMap<Rgb<u8>, usize> {
         let rgb = img.to_rgb8();
         // let mut pixels = Vec::<PColor>::new();
         let mut pix = HashMap::<Rgb<u8>, usize>::new();
@@ -223,10 +400,7 @@ impl Palette {
             let local_p = Rgb([pixel[0], pixel[1], pixel[2]]);
             *pix.entry(local_p).or_insert(0) += 1
         }
-
-        println!("palette extracted");
-
-        Some(pix)
+        pix
     }
 
     fn get_file_name(path: &str) -> String {
@@ -252,50 +426,4 @@ impl Palette {
         }
         Ok(())
     }
-
-    fn output_palette(&mut self, pal_name: &str) {
-        println!("Output palette");
-        let square_size = 64.;
-        let margin = 16.;
-        let width = 512;
-        let colors = self.top_rgb.clone();
-        let height = ((margin + square_size) * ((colors.len() as f32) / 5.) + margin) as i32;
-
-        let mut dt = DrawTarget::new(width, height);
-
-        let mut pb = PathBuilder::new();
-        // pb.move_to(current_x, current_y);
-        pb.rect(0., 0., width as f32, height as f32);
-        pb.close();
-        let path = pb.finish();
-        // let solid = SolidSource::from_unpremultiplied_argb(0xff, 0, 0, 0);
-        let solid = SolidSource::from_unpremultiplied_argb(0xff, 0xff, 0xff, 0xff);
-        dt.fill(&path, &Source::Solid(solid), &DrawOptions::new());
-
-        let mut current_x = 0.;
-        let mut current_y = margin;
-        let col_count = 5;
-        let mut current_col = 0;
-
-        for color in colors {
-            // println!("Draw color: {color}");
-            current_x += margin;
-            let mut pb = PathBuilder::new();
-            // pb.move_to(current_x, current_y);
-            pb.rect(current_x, current_y, square_size, square_size);
-            pb.close();
-            let path = pb.finish();
-            let solid = SolidSource::from_unpremultiplied_argb(0xff, color[0], color[1], color[2]);
-            dt.fill(&path, &Source::Solid(solid), &DrawOptions::new());
-            current_col += 1;
-            if current_col > col_count {
-                current_col = 0;
-                current_x = 0.;
-                current_y += square_size + margin;
-            } else {
-                current_x += square_size;
-            }
-        }
-        let _ = dt.write_png(format!("palettes/{pal_name}.png"));
-    }
 }
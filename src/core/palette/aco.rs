@@ -0,0 +1,135 @@
+use std::io;
+
+use super::bytes::{ByteReader, invalid, utf16_with_null};
+use super::{Palette, PaletteFormat};
+use crate::core::color::{ColorUtil, HSV, Lab, Rgb};
+
+const RGB_SPACE: u16 = 0;
+const HSB_SPACE: u16 = 1;
+const CMYK_SPACE: u16 = 2;
+const LAB_SPACE: u16 = 7;
+const GRAY_SPACE: u16 = 8;
+
+pub(super) struct Aco;
+
+impl PaletteFormat for Aco {
+    fn name(&self) -> &'static str {
+        "Photoshop color swatches"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["aco"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_aco(bytes)
+    }
+
+    fn write(&self, palette: &Palette, _palette_name: &str) -> io::Result<Vec<u8>> {
+        Ok(palette.to_aco())
+    }
+}
+
+impl Palette {
+    /// Photoshop color swatches, a version 1 section followed by a version 2
+    /// section that adds the names.
+    pub fn to_aco(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for version in [1u16, 2] {
+            out.extend(version.to_be_bytes());
+            out.extend((self.top_rgb.len() as u16).to_be_bytes());
+            for c in &self.top_rgb {
+                out.extend(RGB_SPACE.to_be_bytes());
+                for v in c.0 {
+                    out.extend((v as u16 * 257).to_be_bytes());
+                }
+                out.extend(0u16.to_be_bytes());
+                if version == 2 {
+                    let name = self.color_name(c);
+                    out.extend(((name.encode_utf16().count() + 1) as u32).to_be_bytes());
+                    out.extend(utf16_with_null(&name));
+                }
+            }
+        }
+        out
+    }
+
+    /// Uses the version 2 section when present for its names.
+    pub fn from_aco(bytes: &[u8]) -> io::Result<Palette> {
+        let mut r = ByteReader::new(bytes);
+        let mut colors = Vec::new();
+        while !r.is_empty() {
+            let version = r.u16()?;
+            if version != 1 && version != 2 {
+                return Err(invalid("unknown ACO version"));
+            }
+            let count = r.u16()?;
+            let mut section = Vec::new();
+            for _ in 0..count {
+                let space = r.u16()?;
+                let w = [r.u16()?, r.u16()?, r.u16()?, r.u16()?];
+                let name = if version == 2 {
+                    let len = r.u32()? as usize;
+                    Some(r.utf16(len)?)
+                } else {
+                    None
+                };
+                section.push((Self::aco_color(space, w)?, name));
+            }
+            colors = section;
+        }
+        Ok(Palette::from_colors("", colors))
+    }
+
+    fn aco_color(space: u16, w: [u16; 4]) -> io::Result<Rgb<u8>> {
+        let unit = |v: u16| v as f32 / 65535.;
+        Ok(match space {
+            RGB_SPACE => Rgb([w[0], w[1], w[2]].map(|v| (v / 257) as u8)),
+            HSB_SPACE => HSV::to_rgb(unit(w[0]) * 360., unit(w[1]), unit(w[2])),
+            // 0 is full ink
+            CMYK_SPACE => ColorUtil::cmyk_to_rgb(
+                1. - unit(w[0]),
+                1. - unit(w[1]),
+                1. - unit(w[2]),
+                1. - unit(w[3]),
+            ),
+            LAB_SPACE => Lab {
+                l: w[0] as f32 / 100.,
+                a: w[1] as i16 as f32 / 100.,
+                b: w[2] as i16 as f32 / 100.,
+            }
+            .to_rgb(),
+            // Gray is ink coverage too, 0 is white
+            GRAY_SPACE => {
+                let v = 255 - (w[0].min(10000) as u32 * 255 / 10000) as u8;
+                Rgb([v, v, v])
+            }
+            _ => return Err(invalid("unsupported ACO color space")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aco_round_trip_keeps_names() {
+        let palette = Palette::from_colors(
+            "",
+            vec![
+                (Rgb([12, 34, 56]), Some("Deep".to_string())),
+                (Rgb([255, 255, 255]), Some("Paper".to_string())),
+            ],
+        );
+        let again = Palette::from_aco(&palette.to_aco()).unwrap();
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert_eq!(again.color_name(&Rgb([12, 34, 56])), "Deep");
+
+        // Version 1 only still loads, without names
+        let v1_len = 4 + palette.top_rgb.len() * 10;
+        let v1 = Palette::from_aco(&palette.to_aco()[..v1_len]).unwrap();
+        assert_eq!(v1.top_rgb, palette.top_rgb);
+        assert!(v1.color_names.is_empty());
+    }
+}
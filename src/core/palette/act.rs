@@ -0,0 +1,91 @@
+use std::io;
+
+use super::bytes::{ByteReader, invalid};
+use super::{Palette, PaletteFormat};
+use crate::core::color::Rgb;
+
+const ACT_COLORS: usize = 256;
+/// 256 RGB triples.
+const ACT_TABLE_LEN: usize = ACT_COLORS * 3;
+/// Table followed by a color count and a transparent index.
+const ACT_EXTENDED_LEN: usize = ACT_TABLE_LEN + 4;
+const NO_TRANSPARENCY: u16 = 0xFFFF;
+
+/// Adobe Color Table, as saved by Photoshop's indexed color mode.
+pub(super) struct Act;
+
+impl PaletteFormat for Act {
+    fn name(&self) -> &'static str {
+        "Adobe Color Table"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["act"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_act(bytes)
+    }
+
+    fn write(&self, palette: &Palette, _palette_name: &str) -> io::Result<Vec<u8>> {
+        palette.to_act()
+    }
+}
+
+impl Palette {
+    /// Always writes the 772 byte layout so the color count survives.
+    pub fn to_act(&self) -> io::Result<Vec<u8>> {
+        if self.top_rgb.len() > ACT_COLORS {
+            return Err(invalid("Adobe Color Tables hold at most 256 colors"));
+        }
+        let mut out: Vec<u8> = self.top_rgb.iter().flat_map(|c| c.0).collect();
+        out.resize(ACT_TABLE_LEN, 0);
+        out.extend((self.top_rgb.len() as u16).to_be_bytes());
+        out.extend(NO_TRANSPARENCY.to_be_bytes());
+        Ok(out)
+    }
+
+    /// The 768 byte layout has no count, so all 256 entries are kept.
+    pub fn from_act(bytes: &[u8]) -> io::Result<Palette> {
+        let mut r = ByteReader::new(bytes);
+        let table = r.take(ACT_TABLE_LEN)?;
+        let count = match bytes.len() {
+            ACT_TABLE_LEN => ACT_COLORS,
+            ACT_EXTENDED_LEN => {
+                let count = r.u16()? as usize;
+                // Some writers store 0 for a full table
+                if count == 0 || count > ACT_COLORS {
+                    ACT_COLORS
+                } else {
+                    count
+                }
+            }
+            _ => return Err(invalid("Adobe Color Tables are 768 or 772 bytes")),
+        };
+        let colors = table
+            .chunks_exact(3)
+            .take(count)
+            .map(|c| (Rgb([c[0], c[1], c[2]]), None))
+            .collect();
+        Ok(Palette::from_colors("", colors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn act_round_trip_keeps_count() {
+        let palette =
+            Palette::from_colors("", vec![(Rgb([1, 2, 3]), None), (Rgb([250, 128, 0]), None)]);
+        let bytes = palette.to_act().unwrap();
+        assert_eq!(bytes.len(), ACT_EXTENDED_LEN);
+        assert_eq!(Palette::from_act(&bytes).unwrap().top_rgb, palette.top_rgb);
+
+        // Without the count every entry is a color
+        let short = Palette::from_act(&bytes[..ACT_TABLE_LEN]).unwrap();
+        assert_eq!(short.top_rgb.len(), ACT_COLORS);
+        assert!(Palette::from_act(&bytes[..100]).is_err());
+    }
+}
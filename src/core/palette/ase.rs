@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use super::bytes::{ByteReader, invalid, utf16_with_null};
+use super::{Palette, PaletteFormat};
+use crate::core::color::{ColorUtil, Lab, Rgb};
+
+const ASE_SIGNATURE: &[u8; 4] = b"ASEF";
+const GROUP_START: u16 = 0xC001;
+const GROUP_END: u16 = 0xC002;
+const COLOR_ENTRY: u16 = 0x0001;
+
+/// How Illustrator and InDesign treat a swatch, stored after its color values.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) enum SwatchType {
+    Global,
+    Spot,
+    #[default]
+    Process,
+}
+
+impl SwatchType {
+    fn from_word(word: u16) -> Self {
+        match word {
+            0 => SwatchType::Global,
+            1 => SwatchType::Spot,
+            _ => SwatchType::Process,
+        }
+    }
+
+    fn word(self) -> u16 {
+        match self {
+            SwatchType::Global => 0,
+            SwatchType::Spot => 1,
+            SwatchType::Process => 2,
+        }
+    }
+}
+
+pub(super) struct Ase;
+
+impl PaletteFormat for Ase {
+    fn name(&self) -> &'static str {
+        "Adobe Swatch Exchange"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ase"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_ase(bytes)
+    }
+
+    fn write(&self, palette: &Palette, palette_name: &str) -> io::Result<Vec<u8>> {
+        Ok(palette.to_ase(palette_name))
+    }
+}
+
+impl Palette {
+    pub fn swatch_type(&self, c: &Rgb<u8>) -> SwatchType {
+        self.swatch_types
+            .iter()
+            .find(|(typed, _)| typed == c)
+            .map(|(_, t)| *t)
+            .unwrap_or_default()
+    }
+
+    pub fn swatch_group(&self, c: &Rgb<u8>) -> Option<&str> {
+        self.swatch_groups
+            .iter()
+            .find(|(grouped, _)| grouped == c)
+            .map(|(_, g)| g.as_str())
+    }
+
+    /// Adobe Swatch Exchange with RGB swatches, keeping the type and group
+    /// they were imported with. Ungrouped colors come first, then one block
+    /// per group. A palette without groups becomes a single group named after
+    /// the palette.
+    pub fn to_ase(&self, palette_name: &str) -> Vec<u8> {
+        let mut groups: Vec<&str> = Vec::new();
+        for c in &self.top_rgb {
+            if let Some(group) = self.swatch_group(c)
+                && !groups.contains(&group)
+            {
+                groups.push(group);
+            }
+        }
+
+        let mut blocks: Vec<(u16, Vec<u8>)> = Vec::new();
+        if groups.is_empty() {
+            blocks.push((GROUP_START, Self::ase_name(palette_name)));
+            blocks.extend(self.top_rgb.iter().map(|c| self.ase_color(c)));
+            blocks.push((GROUP_END, Vec::new()));
+        } else {
+            for c in self
+                .top_rgb
+                .iter()
+                .filter(|c| self.swatch_group(c).is_none())
+            {
+                blocks.push(self.ase_color(c));
+            }
+            for group in groups {
+                blocks.push((GROUP_START, Self::ase_name(group)));
+                for c in self
+                    .top_rgb
+                    .iter()
+                    .filter(|c| self.swatch_group(c) == Some(group))
+                {
+                    blocks.push(self.ase_color(c));
+                }
+                blocks.push((GROUP_END, Vec::new()));
+            }
+        }
+
+        let mut out = ASE_SIGNATURE.to_vec();
+        out.extend(1u16.to_be_bytes());
+        out.extend(0u16.to_be_bytes());
+        out.extend((blocks.len() as u32).to_be_bytes());
+        for (kind, data) in blocks {
+            out.extend(kind.to_be_bytes());
+            out.extend((data.len() as u32).to_be_bytes());
+            out.extend(data);
+        }
+        out
+    }
+
+    fn ase_color(&self, c: &Rgb<u8>) -> (u16, Vec<u8>) {
+        let mut data = Self::ase_name(&self.color_name(c));
+        data.extend(b"RGB ");
+        for v in c.0 {
+            data.extend((v as f32 / 255.).to_be_bytes());
+        }
+        data.extend(self.swatch_type(c).word().to_be_bytes());
+        (COLOR_ENTRY, data)
+    }
+
+    fn ase_name(name: &str) -> Vec<u8> {
+        let units = name.encode_utf16().count() + 1;
+        let mut data = (units as u16).to_be_bytes().to_vec();
+        data.extend(utf16_with_null(name));
+        data
+    }
+
+    /// Reads RGB, LAB, CMYK and Gray swatches of any type. A file that is a
+    /// single group holding every color names the palette, otherwise each
+    /// color remembers its group.
+    pub fn from_ase(bytes: &[u8]) -> io::Result<Palette> {
+        let mut r = ByteReader::new(bytes);
+        if r.take(4)? != ASE_SIGNATURE {
+            return Err(invalid("missing ASEF signature"));
+        }
+        r.skip(4)?; // version
+        let block_count = r.u32()?;
+        let mut group: Option<String> = None;
+        let mut group_names = Vec::new();
+        let mut colors = Vec::new();
+        let mut swatch_types = Vec::new();
+        let mut swatch_groups = Vec::new();
+        for _ in 0..block_count {
+            let kind = r.u16()?;
+            let length = r.u32()? as usize;
+            let mut block = ByteReader::new(r.take(length)?);
+            match kind {
+                GROUP_START => {
+                    let len = block.u16()? as usize;
+                    let name = block.utf16(len)?;
+                    group_names.push(name.clone());
+                    group = Some(name);
+                }
+                GROUP_END => group = None,
+                COLOR_ENTRY => {
+                    let len = block.u16()? as usize;
+                    let name = block.utf16(len)?;
+                    let c = match block.take(4)? {
+                        b"RGB " => {
+                            let [r, g, b] = [block.f32()?, block.f32()?, block.f32()?];
+                            Rgb([r, g, b].map(|v| (v.clamp(0., 1.) * 255.).round() as u8))
+                        }
+                        b"LAB " => Lab {
+                            l: block.f32()? * 100.,
+                            a: block.f32()?,
+                            b: block.f32()?,
+                        }
+                        .to_rgb(),
+                        b"CMYK" => ColorUtil::cmyk_to_rgb(
+                            block.f32()?,
+                            block.f32()?,
+                            block.f32()?,
+                            block.f32()?,
+                        ),
+                        b"Gray" => {
+                            let v = (block.f32()?.clamp(0., 1.) * 255.).round() as u8;
+                            Rgb([v, v, v])
+                        }
+                        _ => return Err(invalid("unknown ASE color model")),
+                    };
+                    let swatch_type = block.u16().map(SwatchType::from_word).unwrap_or_default();
+                    if swatch_type != SwatchType::Process {
+                        swatch_types.push((c, swatch_type));
+                    }
+                    if let Some(group) = &group {
+                        swatch_groups.push((c, group.clone()));
+                    }
+                    colors.push((c, Some(name)));
+                }
+                _ => {}
+            }
+        }
+        let single_group = group_names.len() == 1 && swatch_groups.len() == colors.len();
+        let palette_name = if single_group {
+            swatch_groups.clear();
+            group_names.remove(0)
+        } else {
+            String::new()
+        };
+        let mut palette = Palette::from_colors(&palette_name, colors);
+        palette.swatch_types = swatch_types;
+        palette.swatch_groups = swatch_groups;
+        Ok(palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ase_round_trip_keeps_names() {
+        let palette = Palette::from_colors(
+            "Brand",
+            vec![
+                (Rgb([255, 0, 0]), Some("Signal".to_string())),
+                (Rgb([0, 128, 255]), Some("Sky".to_string())),
+            ],
+        );
+        let again = Palette::from_ase(&palette.to_ase("Brand")).unwrap();
+        assert_eq!(again.palette_name, "Brand");
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert_eq!(again.color_name(&Rgb([0, 128, 255])), "Sky");
+    }
+
+    #[test]
+    fn ase_round_trip_keeps_groups() {
+        let mut palette = Palette::from_colors(
+            "",
+            vec![
+                (Rgb([255, 0, 0]), Some("Red".to_string())),
+                (Rgb([0, 0, 255]), Some("Blue".to_string())),
+                (Rgb([255, 128, 0]), Some("Orange".to_string())),
+                (Rgb([128, 128, 128]), Some("Grey".to_string())),
+            ],
+        );
+        palette.swatch_groups = vec![
+            (Rgb([255, 0, 0]), "Warm".to_string()),
+            (Rgb([0, 0, 255]), "Cool".to_string()),
+            (Rgb([255, 128, 0]), "Warm".to_string()),
+        ];
+        let again = Palette::from_ase(&palette.to_ase("Brand")).unwrap();
+        assert_eq!(again.palette_name, "");
+        // Grouped colors follow their group, the ungrouped one comes first
+        assert_eq!(
+            again.top_rgb,
+            vec![
+                Rgb([128, 128, 128]),
+                Rgb([255, 0, 0]),
+                Rgb([255, 128, 0]),
+                Rgb([0, 0, 255]),
+            ]
+        );
+        assert_eq!(again.swatch_group(&Rgb([255, 128, 0])), Some("Warm"));
+        assert_eq!(again.swatch_group(&Rgb([0, 0, 255])), Some("Cool"));
+        assert_eq!(again.swatch_group(&Rgb([128, 128, 128])), None);
+    }
+
+    #[test]
+    fn reads_cmyk_spot_swatch() {
+        let mut data = Palette::ase_name("Black");
+        data.extend(b"CMYK");
+        for v in [0f32, 0., 0., 1.] {
+            data.extend(v.to_be_bytes());
+        }
+        data.extend(1u16.to_be_bytes());
+        let mut bytes = b"ASEF\0\x01\0\0\0\0\0\x01".to_vec();
+        bytes.extend(COLOR_ENTRY.to_be_bytes());
+        bytes.extend((data.len() as u32).to_be_bytes());
+        bytes.extend(data);
+        let palette = Palette::from_ase(&bytes).unwrap();
+        assert_eq!(palette.top_rgb, vec![Rgb([0, 0, 0])]);
+        assert_eq!(palette.color_name(&Rgb([0, 0, 0])), "Black");
+        assert_eq!(palette.swatch_type(&Rgb([0, 0, 0])), SwatchType::Spot);
+
+        // Re-exporting keeps it a spot color
+        let again = Palette::from_ase(&palette.to_ase("")).unwrap();
+        assert_eq!(again.swatch_type(&Rgb([0, 0, 0])), SwatchType::Spot);
+    }
+}
@@ -0,0 +1,65 @@
+use std::io;
+
+/// Big-endian cursor over the binary swatch formats.
+pub(super) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos + n;
+        let slice = self.bytes.get(self.pos..end).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "swatch file is truncated")
+        })?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn skip(&mut self, n: usize) -> io::Result<()> {
+        self.take(n).map(|_| ())
+    }
+
+    pub fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// `len` UTF-16BE code units, dropping the null terminator.
+    pub fn utf16(&mut self, len: usize) -> io::Result<String> {
+        let units: Vec<u16> = self
+            .take(len * 2)?
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .take_while(|u| *u != 0)
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+/// UTF-16BE code units of `s` followed by a null terminator.
+pub(super) fn utf16_with_null(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|u| u.to_be_bytes())
+        .collect()
+}
+
+pub(super) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
@@ -0,0 +1,101 @@
+use std::io;
+
+use super::{Palette, PaletteFormat};
+use crate::core::color::Rgb;
+
+const GPL_HEADER: &str = "GIMP Palette";
+const GPL_COLUMNS: usize = 8;
+
+pub(super) struct Gpl;
+
+impl PaletteFormat for Gpl {
+    fn name(&self) -> &'static str {
+        "GIMP palette"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["gpl"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_gpl(&String::from_utf8_lossy(bytes))
+    }
+
+    fn write(&self, palette: &Palette, palette_name: &str) -> io::Result<Vec<u8>> {
+        Ok(palette.to_gpl(palette_name).into_bytes())
+    }
+}
+
+impl Palette {
+    /// GIMP palette text: header, Name and Columns, then `r g b name` lines.
+    pub fn to_gpl(&self, palette_name: &str) -> String {
+        let mut out = format!("{GPL_HEADER}\nName: {palette_name}\nColumns: {GPL_COLUMNS}\n#\n");
+        for c in &self.top_rgb {
+            out.push_str(&format!(
+                "{:3} {:3} {:3}\t{}\n",
+                c[0],
+                c[1],
+                c[2],
+                self.color_name(c)
+            ));
+        }
+        out
+    }
+
+    pub fn from_gpl(text: &str) -> io::Result<Palette> {
+        let mut lines = text.lines();
+        if lines.next().map(|l| l.trim()) != Some(GPL_HEADER) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing GIMP Palette header",
+            ));
+        }
+        let mut name = String::new();
+        let mut colors = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+                continue;
+            }
+            if let Some(n) = line.strip_prefix("Name:") {
+                name = n.trim().to_string();
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let mut channel = || -> io::Result<u8> {
+                parts
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, line.to_string()))
+            };
+            let c = Rgb([channel()?, channel()?, channel()?]);
+            let swatch_name = parts.collect::<Vec<&str>>().join(" ");
+            // Aseprite and others write "Untitled" for unnamed swatches
+            let swatch_name = (swatch_name != "Untitled").then_some(swatch_name);
+            colors.push((c, swatch_name));
+        }
+        Ok(Palette::from_colors(&name, colors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpl_round_trip_keeps_names() {
+        let text = "GIMP Palette\nName: Sunset\nColumns: 4\n# comment\n255   0  0\tHot Red\n 10  20 30\tUntitled\n";
+        let palette = Palette::from_gpl(text).unwrap();
+        assert_eq!(palette.palette_name, "Sunset");
+        assert_eq!(palette.top_rgb, vec![Rgb([255, 0, 0]), Rgb([10, 20, 30])]);
+        assert_eq!(palette.color_name(&Rgb([255, 0, 0])), "Hot Red");
+
+        let again = Palette::from_gpl(&palette.to_gpl("Sunset")).unwrap();
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert_eq!(
+            again.color_names[0],
+            (Rgb([255, 0, 0]), "Hot Red".to_string())
+        );
+        assert!(Palette::from_gpl("JASC-PAL\n").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::io;
+
+use super::bytes::invalid;
+use super::{Palette, PaletteFormat};
+use crate::core::color::Rgb;
+
+const JASC_HEADER: &str = "JASC-PAL";
+const JASC_VERSION: &str = "0100";
+
+/// Paint Shop Pro palette, also used by Aseprite and many pixel art tools.
+pub(super) struct JascPal;
+
+impl PaletteFormat for JascPal {
+    fn name(&self) -> &'static str {
+        "JASC palette"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pal"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_jasc(&String::from_utf8_lossy(bytes))
+    }
+
+    fn write(&self, palette: &Palette, _palette_name: &str) -> io::Result<Vec<u8>> {
+        Ok(palette.to_jasc().into_bytes())
+    }
+}
+
+impl Palette {
+    /// Header, version and count, then one `r g b` line per color. The format
+    /// has no names.
+    pub fn to_jasc(&self) -> String {
+        let mut out = format!(
+            "{JASC_HEADER}\r\n{JASC_VERSION}\r\n{}\r\n",
+            self.top_rgb.len()
+        );
+        for c in &self.top_rgb {
+            out.push_str(&format!("{} {} {}\r\n", c[0], c[1], c[2]));
+        }
+        out
+    }
+
+    pub fn from_jasc(text: &str) -> io::Result<Palette> {
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some(JASC_HEADER) {
+            return Err(invalid("missing JASC-PAL header"));
+        }
+        lines.next(); // version
+        let count: usize = lines
+            .next()
+            .and_then(|l| l.parse().ok())
+            .ok_or_else(|| invalid("missing JASC-PAL color count"))?;
+        let mut colors = Vec::new();
+        for line in lines.filter(|l| !l.is_empty()).take(count) {
+            let channels: Vec<u8> = line
+                .split_whitespace()
+                .take(3)
+                .map(|v| v.parse().map_err(|_| invalid(line)))
+                .collect::<io::Result<_>>()?;
+            let [r, g, b] = channels[..] else {
+                return Err(invalid(line));
+            };
+            colors.push((Rgb([r, g, b]), None));
+        }
+        Ok(Palette::from_colors("", colors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jasc_round_trip() {
+        let text = "JASC-PAL\n0100\n2\n255 0 0\n16 32 48\n";
+        let palette = Palette::from_jasc(text).unwrap();
+        assert_eq!(palette.top_rgb, vec![Rgb([255, 0, 0]), Rgb([16, 32, 48])]);
+        let again = Palette::from_jasc(&palette.to_jasc()).unwrap();
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert!(Palette::from_jasc("GIMP Palette\n").is_err());
+    }
+}
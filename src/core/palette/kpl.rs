@@ -0,0 +1,160 @@
+use std::io::{self, Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::bytes::invalid;
+use super::{Palette, PaletteFormat};
+use crate::core::color::{ColorUtil, Lab, Rgb};
+use crate::core::swatch_sheet::xml_escape;
+
+const KPL_MIMETYPE: &[u8] = b"application/x-krita-palette";
+const KPL_COLUMNS: usize = 8;
+
+/// Krita palette: a zip holding a mimetype entry and a `colorset.xml`.
+pub(super) struct Kpl;
+
+impl PaletteFormat for Kpl {
+    fn name(&self) -> &'static str {
+        "Krita palette"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["kpl"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_kpl(bytes)
+    }
+
+    fn write(&self, palette: &Palette, palette_name: &str) -> io::Result<Vec<u8>> {
+        palette.to_kpl(palette_name)
+    }
+}
+
+impl Palette {
+    pub fn to_kpl(&self, palette_name: &str) -> io::Result<Vec<u8>> {
+        let rows = self.top_rgb.len().div_ceil(KPL_COLUMNS).max(1);
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ColorSet version=\"2.0\" name=\"{}\" columns=\"{KPL_COLUMNS}\" rows=\"{rows}\" comment=\"\" readonly=\"false\">\n",
+            xml_escape(palette_name)
+        );
+        for (i, c) in self.top_rgb.iter().enumerate() {
+            let [r, g, b] = c.0.map(|v| v as f32 / 255.);
+            xml.push_str(&format!(
+                " <ColorSetEntry name=\"{}\" id=\"{}\" spot=\"false\" bitdepth=\"U8\">\n  <RGB r=\"{r}\" g=\"{g}\" b=\"{b}\" space=\"sRGB-elle-V2-srgbtrc.icc\"/>\n  <Position row=\"{}\" column=\"{}\"/>\n </ColorSetEntry>\n",
+                xml_escape(&self.color_name(c)),
+                i + 1,
+                i / KPL_COLUMNS,
+                i % KPL_COLUMNS
+            ));
+        }
+        xml.push_str("</ColorSet>\n");
+        let profiles = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Profiles/>\n";
+
+        // The mimetype must come first and stay uncompressed
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data, method) in [
+            ("mimetype", KPL_MIMETYPE, CompressionMethod::Stored),
+            ("colorset.xml", xml.as_bytes(), CompressionMethod::Deflated),
+            (
+                "profiles.xml",
+                profiles.as_bytes(),
+                CompressionMethod::Deflated,
+            ),
+        ] {
+            let options = SimpleFileOptions::default().compression_method(method);
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(data)?;
+        }
+        Ok(zip.finish().map_err(zip_error)?.into_inner())
+    }
+
+    /// Reads RGB, Lab, CMYK and Gray entries, including those inside groups.
+    pub fn from_kpl(bytes: &[u8]) -> io::Result<Palette> {
+        let mut zip = ZipArchive::new(Cursor::new(bytes)).map_err(zip_error)?;
+        let mut xml = Vec::new();
+        zip.by_name("colorset.xml")
+            .map_err(zip_error)?
+            .read_to_end(&mut xml)?;
+        let xml = String::from_utf8_lossy(&xml);
+        let doc = roxmltree::Document::parse(&xml).map_err(|e| invalid(&e.to_string()))?;
+        let root = doc.root_element();
+        if !root.has_tag_name("ColorSet") {
+            return Err(invalid("missing ColorSet element"));
+        }
+        let mut colors = Vec::new();
+        for entry in root
+            .descendants()
+            .filter(|n| n.has_tag_name("ColorSetEntry"))
+        {
+            let Some(model) = entry.children().find(|n| n.is_element()) else {
+                continue;
+            };
+            let v = |attr: &str| -> io::Result<f32> {
+                model
+                    .attribute(attr)
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| invalid(&format!("missing {attr} attribute")))
+            };
+            let unit = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
+            let c = match model.tag_name().name() {
+                "RGB" | "sRGB" => Rgb([unit(v("r")?), unit(v("g")?), unit(v("b")?)]),
+                "Lab" => Lab {
+                    l: v("L")?,
+                    a: v("a")?,
+                    b: v("b")?,
+                }
+                .to_rgb(),
+                "CMYK" => ColorUtil::cmyk_to_rgb(v("c")?, v("m")?, v("y")?, v("k")?),
+                "Gray" => {
+                    let g = unit(v("g")?);
+                    Rgb([g, g, g])
+                }
+                _ => continue,
+            };
+            let name = entry.attribute("name").filter(|n| !n.is_empty());
+            colors.push((c, name.map(str::to_string)));
+        }
+        Ok(Palette::from_colors(
+            root.attribute("name").unwrap_or_default(),
+            colors,
+        ))
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> io::Error {
+    invalid(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kpl_round_trip_keeps_names() {
+        let palette = Palette::from_colors(
+            "Studio",
+            vec![
+                (Rgb([255, 0, 0]), Some("Red & Co".to_string())),
+                (Rgb([0, 64, 128]), Some("Navy".to_string())),
+            ],
+        );
+        let again = Palette::from_kpl(&palette.to_kpl("Studio").unwrap()).unwrap();
+        assert_eq!(again.palette_name, "Studio");
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert_eq!(again.color_name(&Rgb([255, 0, 0])), "Red & Co");
+    }
+
+    #[test]
+    fn reads_deflated_colorset() {
+        let xml = r#"<ColorSet name="Gray"><Group name="g"><ColorSetEntry name="Mid"><Gray g="0.5"/></ColorSetEntry></Group></ColorSet>"#;
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file("colorset.xml", options).unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+        let palette = Palette::from_kpl(&bytes).unwrap();
+        assert_eq!(palette.top_rgb, vec![Rgb([128, 128, 128])]);
+        assert_eq!(palette.color_name(&Rgb([128, 128, 128])), "Mid");
+    }
+}
@@ -0,0 +1,113 @@
+use std::io;
+
+use super::bytes::invalid;
+use super::{Palette, PaletteFormat};
+use crate::core::color::Rgb;
+
+/// Paint.NET shows at most this many palette entries.
+const PAINT_NET_COLORS: usize = 96;
+
+/// Paint.NET palette text, one `AARRGGBB` hex value per line. Shares `.txt`
+/// with "Save as Text", so `#RRGGBB` lines are read too.
+pub(super) struct PaintNet;
+
+impl PaletteFormat for PaintNet {
+    fn name(&self) -> &'static str {
+        "Paint.NET palette"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["txt"]
+    }
+
+    fn read(&self, bytes: &[u8]) -> io::Result<Palette> {
+        Palette::from_paint_net(&String::from_utf8_lossy(bytes))
+    }
+
+    fn write(&self, palette: &Palette, palette_name: &str) -> io::Result<Vec<u8>> {
+        Ok(palette.to_paint_net(palette_name)?.into_bytes())
+    }
+}
+
+impl Palette {
+    /// Imported names go in a comment above their color since the format has
+    /// no place for them.
+    pub fn to_paint_net(&self, palette_name: &str) -> io::Result<String> {
+        if self.top_rgb.len() > PAINT_NET_COLORS {
+            return Err(invalid("Paint.NET palettes hold at most 96 colors"));
+        }
+        let mut out = format!(";paint.net Palette File\n;Palette Name: {palette_name}\n");
+        out.push_str(&format!(";Colors: {}\n", self.top_rgb.len()));
+        for c in &self.top_rgb {
+            if let Some((_, name)) = self.color_names.iter().find(|(named, _)| named == c) {
+                out.push_str(&format!(";{name}\n"));
+            }
+            out.push_str(&format!("FF{:02X}{:02X}{:02X}\n", c[0], c[1], c[2]));
+        }
+        Ok(out)
+    }
+
+    /// Fully transparent entries are padding and skipped.
+    pub fn from_paint_net(text: &str) -> io::Result<Palette> {
+        let mut name = String::new();
+        let mut colors = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(comment) = line.strip_prefix(';') {
+                if let Some(n) = comment.trim().strip_prefix("Palette Name:") {
+                    name = n.trim().to_string();
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let hex = line.strip_prefix('#').unwrap_or(line);
+            let argb = match hex.len() {
+                8 => u32::from_str_radix(hex, 16),
+                6 => u32::from_str_radix(hex, 16).map(|v| 0xFF00_0000 | v),
+                _ => return Err(invalid(line)),
+            }
+            .map_err(|_| invalid(line))?;
+            if argb >> 24 == 0 {
+                continue;
+            }
+            let [_, r, g, b] = argb.to_be_bytes();
+            colors.push((Rgb([r, g, b]), None));
+        }
+        if colors.is_empty() {
+            return Err(invalid("no Paint.NET colors found"));
+        }
+        Ok(Palette::from_colors(&name, colors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_net_round_trip() {
+        let text = ";paint.net Palette File\n;Palette Name: Dusk\nFFFF0000\nff102030\n00FFFFFF\n";
+        let palette = Palette::from_paint_net(text).unwrap();
+        assert_eq!(palette.palette_name, "Dusk");
+        assert_eq!(palette.top_rgb, vec![Rgb([255, 0, 0]), Rgb([16, 32, 48])]);
+        let again = Palette::from_paint_net(&palette.to_paint_net("Dusk").unwrap()).unwrap();
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        let named = Palette::from_colors("", vec![(Rgb([255, 0, 0]), Some("Red".to_string()))]);
+        assert!(
+            named
+                .to_paint_net("")
+                .unwrap()
+                .ends_with(";Red\nFFFF0000\n")
+        );
+        let too_many = Palette::from_colors("", vec![(Rgb([0, 0, 0]), None); 97]);
+        assert!(too_many.to_paint_net("").is_err());
+        assert!(Palette::from_paint_net("not a color\n").is_err());
+
+        // Hex lists written by "Save as Text" share the extension
+        let text = include_str!("../../../pallette.txt");
+        let saved = Palette::from_paint_net(text).unwrap();
+        assert_eq!(saved.top_rgb[0], Rgb([0x79, 0x83, 0x88]));
+        assert_eq!(saved.top_rgb.len(), text.lines().count());
+    }
+}
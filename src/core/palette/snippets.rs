@@ -0,0 +1,300 @@
+use std::{fs, io, path::Path};
+
+use super::Palette;
+use crate::core::color::ColorUtil;
+
+/// User templates are read from here, one `<name>.<ext>.tmpl` file each.
+pub(crate) const TEMPLATE_DIR: &str = "templates";
+
+const RUST_COLOR32: &str = "use egui::Color32;
+
+pub const {{PALETTE}}: [Color32; {{count}}] = [
+{{#colors}}    Color32::from_rgb({{r}}, {{g}}, {{b}}), // {{name}}
+{{/colors}}];
+";
+
+const RUST_U8: &str = "pub const {{PALETTE}}: [[u8; 3]; {{count}}] = [
+{{#colors}}    [{{r}}, {{g}}, {{b}}], // {{name}}
+{{/colors}}];
+";
+
+const C_HEADER: &str = "#ifndef {{PALETTE}}_H
+#define {{PALETTE}}_H
+
+#include <stdint.h>
+
+#define {{PALETTE}}_COUNT {{count}}
+
+static const uint8_t {{palette}}[{{PALETTE}}_COUNT][3] = {
+{{#colors}}    { {{r}}, {{g}}, {{b}} }, /* {{name}} */
+{{/colors}}};
+
+#endif
+";
+
+const GLSL_SRGB: &str = "// sRGB encoded
+const vec3 {{palette}}[{{count}}] = vec3[](
+{{#colors}}    vec3({{rf}}, {{gf}}, {{bf}}){{sep}} // {{name}}
+{{/colors}});
+";
+
+const GLSL_LINEAR: &str = "// Linear, ready for lighting math
+const vec3 {{palette}}[{{count}}] = vec3[](
+{{#colors}}    vec3({{lr}}, {{lg}}, {{lb}}){{sep}} // {{name}}
+{{/colors}});
+";
+
+const HLSL_SRGB: &str = "// sRGB encoded
+static const float3 {{palette}}[{{count}}] = {
+{{#colors}}    float3({{rf}}, {{gf}}, {{bf}}){{sep}} // {{name}}
+{{/colors}}};
+";
+
+const HLSL_LINEAR: &str = "// Linear, ready for lighting math
+static const float3 {{palette}}[{{count}}] = {
+{{#colors}}    float3({{lr}}, {{lg}}, {{lb}}){{sep}} // {{name}}
+{{/colors}}};
+";
+
+const PYTHON: &str = "{{PALETTE}} = [
+{{#colors}}    ({{r}}, {{g}}, {{b}}),  # {{name}}
+{{/colors}}]
+";
+
+const LUA: &str = "-- 0-1 channels as love.graphics.setColor expects
+local {{palette}} = {
+{{#colors}}  { {{rf}}, {{gf}}, {{bf}} }, -- {{hex}} {{name}}
+{{/colors}}}
+
+return {{palette}}
+";
+
+/// A text template rendered once per palette, with a block repeated per color.
+///
+/// Palette placeholders: `{{palette}}` (snake case), `{{PALETTE}}` (upper
+/// snake case), `{{title}}` (as typed) and `{{count}}`. Between `{{#colors}}`
+/// and `{{/colors}}`: `{{i}}`, `{{r}}`, `{{g}}`, `{{b}}`, `{{hex}}`
+/// (`#rrggbb`), `{{HEX}}` (`RRGGBB`), `{{rf}}`, `{{gf}}`, `{{bf}}` (sRGB
+/// 0-1), `{{lr}}`, `{{lg}}`, `{{lb}}` (linear 0-1), `{{name}}`, `{{ident}}`,
+/// `{{IDENT}}` and `{{sep}}`, a comma on all but the last color.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct CodeTemplate {
+    pub name: String,
+    pub extension: String,
+    pub body: String,
+}
+
+impl CodeTemplate {
+    pub fn builtin() -> Vec<CodeTemplate> {
+        [
+            ("Rust Color32", "rs", RUST_COLOR32),
+            ("Rust [u8; 3]", "rs", RUST_U8),
+            ("C header", "h", C_HEADER),
+            ("GLSL vec3 (sRGB)", "glsl", GLSL_SRGB),
+            ("GLSL vec3 (linear)", "glsl", GLSL_LINEAR),
+            ("HLSL float3 (sRGB)", "hlsl", HLSL_SRGB),
+            ("HLSL float3 (linear)", "hlsl", HLSL_LINEAR),
+            ("Python list", "py", PYTHON),
+            ("Lua table", "lua", LUA),
+        ]
+        .into_iter()
+        .map(|(name, extension, body)| CodeTemplate {
+            name: name.to_string(),
+            extension: extension.to_string(),
+            body: body.to_string(),
+        })
+        .collect()
+    }
+
+    /// Built-in templates followed by the ones in `TEMPLATE_DIR`.
+    pub fn all() -> Vec<CodeTemplate> {
+        let mut templates = Self::builtin();
+        match Self::load_dir(Path::new(TEMPLATE_DIR)) {
+            Ok(user) => templates.extend(user),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("Error loading templates {}", e),
+        }
+        templates
+    }
+
+    /// `godot.gd.tmpl` becomes the template "godot.gd" writing `.gd` files.
+    /// Unreadable or non UTF-8 files are reported and skipped.
+    fn load_dir(dir: &Path) -> io::Result<Vec<CodeTemplate>> {
+        let mut templates = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".tmpl"))
+            else {
+                continue;
+            };
+            let extension = Path::new(name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("txt");
+            let body = match fs::read_to_string(&path) {
+                Ok(body) => body,
+                Err(e) => {
+                    eprintln!("Error loading template {} {}", path.display(), e);
+                    continue;
+                }
+            };
+            templates.push(CodeTemplate {
+                name: name.to_string(),
+                extension: extension.to_string(),
+                body,
+            });
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    pub fn render(&self, palette: &Palette, palette_name: &str) -> String {
+        let (head, block, tail) = match self.body.split_once("{{#colors}}") {
+            Some((head, rest)) => match rest.split_once("{{/colors}}") {
+                Some((block, tail)) => (head, block, tail),
+                None => (head, rest, ""),
+            },
+            None => (self.body.as_str(), "", ""),
+        };
+
+        let ident = match identifier(palette_name) {
+            s if s.is_empty() => "palette".to_string(),
+            s => s,
+        };
+        let count = palette.top_rgb.len().to_string();
+        let palette_vars = [
+            ("palette", ident.clone()),
+            ("PALETTE", ident.to_uppercase()),
+            ("title", palette_name.to_string()),
+            ("count", count),
+        ];
+
+        let mut out = fill(head, &palette_vars);
+        for (i, c) in palette.top_rgb.iter().enumerate() {
+            let name = palette.color_name(c);
+            let hex = ColorUtil::rgb_to_hex(*c);
+            let [rf, gf, bf] = c.0.map(|v| float(v as f32 / 255.));
+            let [lr, lg, lb] = c.0.map(|v| float(ColorUtil::srgb_to_linear(v)));
+            let color_vars = [
+                ("i", i.to_string()),
+                ("r", c[0].to_string()),
+                ("g", c[1].to_string()),
+                ("b", c[2].to_string()),
+                ("hex", hex.to_lowercase()),
+                ("HEX", hex[1..].to_string()),
+                ("rf", rf),
+                ("gf", gf),
+                ("bf", bf),
+                ("lr", lr),
+                ("lg", lg),
+                ("lb", lb),
+                ("ident", identifier(&name)),
+                ("IDENT", identifier(&name).to_uppercase()),
+                ("name", name),
+                (
+                    "sep",
+                    if i + 1 < palette.top_rgb.len() {
+                        ","
+                    } else {
+                        ""
+                    }
+                    .to_string(),
+                ),
+            ];
+            out.push_str(&fill(&fill(block, &color_vars), &palette_vars));
+        }
+        out.push_str(&fill(tail, &palette_vars));
+        out
+    }
+}
+
+fn fill(text: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(text.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{{{key}}}}}"), value)
+    })
+}
+
+/// Snake case usable as an identifier in any of the target languages.
+fn identifier(s: &str) -> String {
+    let ident = s
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("_");
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("c_{ident}")
+    } else {
+        ident
+    }
+}
+
+/// Always has a decimal point, so shaders read it as a float.
+fn float(v: f32) -> String {
+    let s = format!("{v:.4}");
+    let s = s.trim_end_matches('0');
+    if s.ends_with('.') {
+        format!("{s}0")
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::color::Rgb;
+
+    #[test]
+    fn renders_builtin_templates() {
+        let palette = Palette::from_colors(
+            "Night Sky",
+            vec![
+                (Rgb([255, 0, 0]), Some("Hot Red".to_string())),
+                (Rgb([0, 0, 0]), Some("Ink".to_string())),
+            ],
+        );
+        let templates = CodeTemplate::builtin();
+        let render = |name: &str| {
+            templates
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap()
+                .render(&palette, "Night Sky")
+        };
+        assert!(render("Rust Color32").contains(
+            "pub const NIGHT_SKY: [Color32; 2] = [\n    Color32::from_rgb(255, 0, 0), // Hot Red\n"
+        ));
+        assert!(render("C header").contains("static const uint8_t night_sky[NIGHT_SKY_COUNT][3]"));
+        let glsl = render("GLSL vec3 (linear)");
+        assert!(glsl.contains("vec3(1.0, 0.0, 0.0), // Hot Red\n    vec3(0.0, 0.0, 0.0) // Ink"));
+        assert!(render("Lua table").contains("{ 1.0, 0.0, 0.0 }, -- #ff0000 Hot Red"));
+
+        let custom = CodeTemplate {
+            name: "names".to_string(),
+            extension: "txt".to_string(),
+            body: "{{title}}:{{#colors}} {{IDENT}}={{HEX}}{{sep}}{{/colors}}".to_string(),
+        };
+        assert_eq!(
+            custom.render(&palette, "Night Sky"),
+            "Night Sky: HOT_RED=FF0000, INK=000000"
+        );
+    }
+
+    #[test]
+    fn bad_template_files_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("snippet_templates_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("good.gd.tmpl"), "{{#colors}}{{hex}}{{/colors}}").unwrap();
+        fs::write(dir.join("binary.txt.tmpl"), [0xff, 0xfe, 0x00]).unwrap();
+        fs::write(dir.join("notes.md"), "not a template").unwrap();
+        let templates = CodeTemplate::load_dir(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "good.gd");
+        assert_eq!(templates[0].extension, "gd");
+    }
+}
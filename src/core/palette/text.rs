@@ -0,0 +1,172 @@
+use std::io;
+
+use super::Palette;
+use super::bytes::invalid;
+use crate::core::{color::Rgb, css_color::CssColor};
+
+/// Files scanned for color tokens when no palette format claims them, or
+/// when the claiming format can't read them (a `.txt` of hex lines isn't a
+/// Paint.NET palette).
+pub(crate) const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "css", "scss", "sass", "less", "html", "svg", "json", "js", "ts", "rs", "c", "h", "cpp",
+    "hpp", "py", "lua", "glsl", "hlsl", "toml", "yaml", "yml", "md",
+];
+
+const CSS_FUNCTIONS: &[&[u8]] = &[
+    b"rgb(", b"rgba(", b"hsl(", b"hsla(", b"hwb(", b"lab(", b"lch(", b"oklab(", b"oklch(",
+    b"color(",
+];
+
+impl Palette {
+    pub fn is_text_file(path: &str) -> bool {
+        std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| TEXT_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+    }
+
+    /// Collects `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`, `0xRRGGBB` and CSS
+    /// color functions (`rgb()`, `hsl()`, `oklch()`…) in order of appearance,
+    /// without duplicates. Alpha is dropped. A color assigned to a CSS, SCSS or LESS
+    /// variable or a quoted key keeps that name.
+    pub fn from_text(text: &str) -> io::Result<Palette> {
+        let mut colors: Vec<(Rgb<u8>, Option<String>)> = Vec::new();
+        for line in text.lines() {
+            let bytes = line.as_bytes();
+            let mut first = true;
+            let mut i = 0;
+            while i < bytes.len() {
+                let Some((c, end)) = color_token(bytes, i) else {
+                    i += 1;
+                    continue;
+                };
+                let name = if first {
+                    assigned_name(&line[..i])
+                } else {
+                    None
+                };
+                if !colors.iter().any(|(seen, _)| *seen == c) {
+                    colors.push((c, name));
+                }
+                first = false;
+                i = end;
+            }
+        }
+        if colors.is_empty() {
+            return Err(invalid("no colors found in text"));
+        }
+        Ok(Palette::from_colors("", colors))
+    }
+}
+
+fn is_word(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Color starting at `i` and the index just past it.
+fn color_token(bytes: &[u8], i: usize) -> Option<(Rgb<u8>, usize)> {
+    if i > 0 && is_word(bytes[i - 1]) {
+        return None;
+    }
+    let hex_run = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_hexdigit())
+            .count()
+    };
+    let ends_word = |end: usize| bytes.get(end).is_none_or(|b| !is_word(*b));
+
+    if bytes[i] == b'#' {
+        let len = hex_run(i + 1);
+        let end = i + 1 + len;
+        if !ends_word(end) {
+            return None;
+        }
+        let digits = std::str::from_utf8(&bytes[i + 1..end]).ok()?;
+        let channel = |k: usize, width: usize| {
+            let v = u8::from_str_radix(&digits[k * width..(k + 1) * width], 16).ok()?;
+            Some(if width == 1 { v * 17 } else { v })
+        };
+        let width = match len {
+            3 | 4 => 1,
+            6 | 8 => 2,
+            _ => return None,
+        };
+        return Some((
+            Rgb([channel(0, width)?, channel(1, width)?, channel(2, width)?]),
+            end,
+        ));
+    }
+
+    if bytes[i..].starts_with(b"0x") || bytes[i..].starts_with(b"0X") {
+        let end = i + 2 + hex_run(i + 2);
+        if end - i - 2 != 6 || !ends_word(end) {
+            return None;
+        }
+        let v = u32::from_str_radix(std::str::from_utf8(&bytes[i + 2..end]).ok()?, 16).ok()?;
+        let [_, r, g, b] = v.to_be_bytes();
+        return Some((Rgb([r, g, b]), end));
+    }
+
+    let lower: Vec<u8> = bytes[i..]
+        .iter()
+        .take(6)
+        .map(u8::to_ascii_lowercase)
+        .collect();
+    if !CSS_FUNCTIONS.iter().any(|f| lower.starts_with(f)) {
+        return None;
+    }
+    let rest = std::str::from_utf8(&bytes[i..]).ok()?;
+    let close = rest.find(')')?;
+    let c = CssColor::parse(&rest[..=close])?;
+    Some((c, i + close + 1))
+}
+
+/// `--name:`, `$name:`, `@name:` or a quoted key before the color.
+fn assigned_name(prefix: &str) -> Option<String> {
+    let prefix = prefix.trim_end().trim_end_matches(['"', '\'']).trim_end();
+    let prefix = prefix.strip_suffix([':', '='])?.trim_end();
+    let quoted = prefix.ends_with(['"', '\'']);
+    let key = prefix.trim_end_matches(['"', '\'']);
+    let start = key
+        .rfind(|ch: char| !(ch.is_alphanumeric() || "-_$@.".contains(ch)))
+        .map_or(0, |p| p + 1);
+    let key = &key[start..];
+    let name = ["--", "$", "@"]
+        .iter()
+        .find_map(|sigil| key.strip_prefix(sigil))
+        .or(quoted.then_some(key))?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tokens_in_text() {
+        let text = ":root {\n  --brand-red: #ff0000;\n  color: rgb(0 128 255 / 50%);\n}\n$accent: hsl(120deg, 100%, 25%);\nconst C: u32 = 0x102030; // #define, #FF0000 again\n\"bg\": \"#abc\"";
+        let palette = Palette::from_text(text).unwrap();
+        assert_eq!(
+            palette.top_rgb,
+            vec![
+                Rgb([255, 0, 0]),
+                Rgb([0, 128, 255]),
+                Rgb([0, 128, 0]),
+                Rgb([16, 32, 48]),
+                Rgb([170, 187, 204]),
+            ]
+        );
+        assert_eq!(palette.color_name(&Rgb([255, 0, 0])), "brand-red");
+        assert_eq!(palette.color_name(&Rgb([0, 128, 0])), "accent");
+        assert_eq!(palette.color_name(&Rgb([170, 187, 204])), "bg");
+        assert!(Palette::from_text("no colors #here").is_err());
+    }
+
+    #[test]
+    fn loads_hex_list() {
+        let palette = Palette::from_text(include_str!("../../../pallette.txt")).unwrap();
+        assert_eq!(palette.top_rgb[0], Rgb([0x79, 0x83, 0x88]));
+        assert!(palette.color_names.is_empty());
+    }
+}
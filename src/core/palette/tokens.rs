@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+
+use super::Palette;
+use crate::core::{
+    color::{ColorUtil, Rgb},
+    gamut::{Gamut, WideColor},
+};
+
+/// Roles offered when assigning colors, any other string works too.
+pub(crate) const ROLES: &[&str] = &[
+    "primary",
+    "secondary",
+    "accent",
+    "background",
+    "surface",
+    "text",
+    "muted",
+    "border",
+    "success",
+    "warning",
+    "danger",
+    "info",
+];
+
+/// Stylesheet and design token formats for front-end code.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum StyleFormat {
+    #[default]
+    CssVariables,
+    ScssMap,
+    Tailwind,
+    DesignTokens,
+}
+
+impl StyleFormat {
+    pub const ALL: [StyleFormat; 4] = [
+        StyleFormat::CssVariables,
+        StyleFormat::ScssMap,
+        StyleFormat::Tailwind,
+        StyleFormat::DesignTokens,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StyleFormat::CssVariables => "CSS custom properties",
+            StyleFormat::ScssMap => "SCSS map",
+            StyleFormat::Tailwind => "Tailwind theme.colors",
+            StyleFormat::DesignTokens => "Design tokens (DTCG)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StyleFormat::CssVariables => "css",
+            StyleFormat::ScssMap => "scss",
+            StyleFormat::Tailwind => "js",
+            StyleFormat::DesignTokens => "tokens.json",
+        }
+    }
+}
+
+/// How each exported color is named.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TokenNaming {
+    /// Slug of the imported or nearest color name.
+    #[default]
+    Slug,
+    /// Position in the palette, starting at 1.
+    Index,
+    /// Assigned role, falling back to the slug.
+    Role,
+}
+
+impl TokenNaming {
+    pub const ALL: [TokenNaming; 3] = [TokenNaming::Slug, TokenNaming::Index, TokenNaming::Role];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TokenNaming::Slug => "Color names",
+            TokenNaming::Index => "Index",
+            TokenNaming::Role => "Roles",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct TokenSettings {
+    pub naming: TokenNaming,
+    /// Joined to every name with a dash, may be empty.
+    pub prefix: String,
+    /// CSS adds a `color()` override for it, DTCG writes its coordinates.
+    pub gamut: Gamut,
+}
+
+impl Default for TokenSettings {
+    fn default() -> Self {
+        Self {
+            naming: TokenNaming::default(),
+            prefix: "color".to_string(),
+            gamut: Gamut::default(),
+        }
+    }
+}
+
+impl Palette {
+    pub fn role(&self, c: &Rgb<u8>) -> Option<&str> {
+        self.roles
+            .iter()
+            .find(|(rc, _)| rc == c)
+            .map(|(_, r)| r.as_str())
+    }
+
+    /// Assigns `role` to `c`, taking it from any color that had it.
+    pub fn set_role(&mut self, c: Rgb<u8>, role: Option<String>) {
+        self.roles
+            .retain(|(rc, r)| *rc != c && Some(r) != role.as_ref());
+        if let Some(role) = role.filter(|r| !r.is_empty()) {
+            self.roles.push((c, role));
+        }
+    }
+
+    /// One unique, slugified name per color in `top_rgb`.
+    pub fn token_names(&self, settings: &TokenSettings) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (i, c) in self.top_rgb.iter().enumerate() {
+            let base = match settings.naming {
+                TokenNaming::Index => (i + 1).to_string(),
+                TokenNaming::Slug => slug(&self.color_name(c)),
+                TokenNaming::Role => match self.role(c) {
+                    Some(role) => slug(role),
+                    None => slug(&self.color_name(c)),
+                },
+            };
+            let base = match slug(&settings.prefix) {
+                prefix if prefix.is_empty() => base,
+                prefix => format!("{prefix}-{base}"),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while names.contains(&name) {
+                name = format!("{base}-{n}");
+                n += 1;
+            }
+            names.push(name);
+        }
+        names
+    }
+
+    pub fn to_style(
+        &self,
+        format: StyleFormat,
+        settings: &TokenSettings,
+        palette_name: &str,
+    ) -> String {
+        let names = self.token_names(settings);
+        let entries: Vec<(&String, String)> = names
+            .iter()
+            .zip(&self.top_rgb)
+            .map(|(n, c)| (n, ColorUtil::rgb_to_hex(*c).to_lowercase()))
+            .collect();
+        let mut out = String::new();
+        match format {
+            StyleFormat::CssVariables => {
+                out.push_str(":root {\n");
+                for (name, hex) in &entries {
+                    out.push_str(&format!("  --{name}: {hex};\n"));
+                }
+                out.push_str("}\n");
+                if settings.gamut != Gamut::Srgb {
+                    let css = settings.gamut.css_name();
+                    out.push_str(&format!(
+                        "\n@supports (color: color({css} 0 0 0)) {{\n  :root {{\n"
+                    ));
+                    for (name, c) in names.iter().zip(&self.top_rgb) {
+                        let wide = WideColor::from_srgb(*c, settings.gamut);
+                        out.push_str(&format!("    --{name}: {};\n", wide.css()));
+                    }
+                    out.push_str("  }\n}\n");
+                }
+            }
+            StyleFormat::ScssMap => {
+                let map = match slug(palette_name) {
+                    s if s.is_empty() => "palette".to_string(),
+                    s => s,
+                };
+                out.push_str(&format!("${map}: (\n"));
+                for (name, hex) in &entries {
+                    out.push_str(&format!("  \"{name}\": {hex},\n"));
+                }
+                out.push_str(");\n");
+            }
+            StyleFormat::Tailwind => {
+                out.push_str("/** @type {import('tailwindcss').Config} */\nmodule.exports = {\n  theme: {\n    colors: {\n");
+                for (name, hex) in &entries {
+                    out.push_str(&format!("      '{name}': '{hex}',\n"));
+                }
+                out.push_str("    },\n  },\n};\n");
+            }
+            StyleFormat::DesignTokens => {
+                let tokens: serde_json::Map<String, serde_json::Value> = names
+                    .iter()
+                    .zip(&self.top_rgb)
+                    .map(|(name, c)| {
+                        let token = serde_json::json!({
+                            "$type": "color",
+                            "$value": WideColor::from_srgb(*c, settings.gamut).token_value(),
+                        });
+                        (name.clone(), token)
+                    })
+                    .collect();
+                let json = serde_json::json!({ palette_name: tokens });
+                out = serde_json::to_string_pretty(&json).unwrap_or_default();
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Lower case words joined by dashes, `#` and other symbols dropped.
+fn slug(s: &str) -> String {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_formats() {
+        let mut palette = Palette::from_colors(
+            "Brand Kit",
+            vec![
+                (Rgb([255, 0, 0]), Some("Hot Red".to_string())),
+                (Rgb([0, 0, 255]), Some("Hot red!".to_string())),
+                (Rgb([16, 32, 48]), None),
+            ],
+        );
+        palette.set_role(Rgb([0, 0, 255]), Some("primary".to_string()));
+        let mut settings = TokenSettings::default();
+        assert_eq!(
+            palette.token_names(&settings),
+            ["color-hot-red", "color-hot-red-2", "color-102030"]
+        );
+        settings.naming = TokenNaming::Role;
+        settings.prefix.clear();
+        assert_eq!(
+            palette.token_names(&settings),
+            ["hot-red", "primary", "102030"]
+        );
+
+        let css = palette.to_style(StyleFormat::CssVariables, &settings, "Brand Kit");
+        assert!(css.contains("  --primary: #0000ff;\n"));
+        let scss = palette.to_style(StyleFormat::ScssMap, &settings, "Brand Kit");
+        assert!(scss.starts_with("$brand-kit: (\n  \"hot-red\": #ff0000,"));
+        let tailwind = palette.to_style(StyleFormat::Tailwind, &settings, "Brand Kit");
+        assert!(tailwind.contains("      'primary': '#0000ff',\n"));
+        let tokens = palette.to_style(StyleFormat::DesignTokens, &settings, "Brand Kit");
+        let json: serde_json::Value = serde_json::from_str(&tokens).unwrap();
+        assert_eq!(json["Brand Kit"]["primary"]["$type"], "color");
+
+        // The exports read back through the text importer
+        let again = Palette::from_text(&css).unwrap();
+        assert_eq!(again.top_rgb, palette.top_rgb);
+        assert_eq!(again.color_name(&Rgb([0, 0, 255])), "primary");
+    }
+}
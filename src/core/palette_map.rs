@@ -0,0 +1,161 @@
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::core::{color::Rgb, palette::Palette};
+
+/// Result of `PaletteMap::recolor_dir`.
+pub(crate) struct RecolorReport {
+    pub written: usize,
+    /// Files that could not be read or written, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// A source -> target color mapping used to recolor images.
+/// Pixels whose color is not in the mapping are left untouched.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct PaletteMap {
+    pub map_name: String,
+    pub entries: Vec<(Rgb<u8>, Rgb<u8>)>,
+}
+
+impl PaletteMap {
+    /// Pairs the colors of two palettes by index.
+    pub fn from_palettes(source: &Palette, target: &Palette) -> Self {
+        let entries = source
+            .top_rgb
+            .iter()
+            .zip(target.top_rgb.iter())
+            .map(|(s, t)| (*s, *t))
+            .collect();
+        Self {
+            map_name: format!("{} to {}", source.palette_name, target.palette_name),
+            entries,
+        }
+    }
+
+    fn lookup(&self) -> HashMap<Rgb<u8>, Rgb<u8>> {
+        // First entry wins if a source color is mapped twice
+        let mut lookup = HashMap::new();
+        for (source, target) in &self.entries {
+            lookup.entry(*source).or_insert(*target);
+        }
+        lookup
+    }
+
+    pub fn recolor_image(&self, img: &RgbaImage) -> RgbaImage {
+        let lookup = self.lookup();
+        let mut out = img.clone();
+        for pixel in out.pixels_mut() {
+            let c = Rgb([pixel[0], pixel[1], pixel[2]]);
+            if let Some(t) = lookup.get(&c) {
+                pixel[0] = t[0];
+                pixel[1] = t[1];
+                pixel[2] = t[2];
+            }
+        }
+        out
+    }
+
+    /// Recolors every image in `input_dir` and writes copies with the same
+    /// file names to `output_dir`. A file that fails is recorded in the report
+    /// and the batch carries on with the rest.
+    pub fn recolor_dir(
+        &self,
+        input_dir: &Path,
+        output_dir: &Path,
+    ) -> Result<RecolorReport, Box<dyn Error>> {
+        fs::create_dir_all(output_dir)?;
+        let mut report = RecolorReport {
+            written: 0,
+            failed: Vec::new(),
+        };
+        for entry in fs::read_dir(input_dir)? {
+            let path = entry?.path();
+            if !path.is_file() || ImageFormat::from_path(&path).is_err() {
+                continue;
+            }
+            let Some(f_name) = path.file_name() else {
+                continue;
+            };
+            match self.recolor_file(&path, &output_dir.join(f_name)) {
+                Ok(()) => report.written += 1,
+                Err(e) => report.failed.push((path, e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
+    fn recolor_file(&self, input: &Path, output: &Path) -> image::ImageResult<()> {
+        let img = image::open(input)?;
+        let has_alpha = img.color().has_alpha();
+        let recolored = DynamicImage::ImageRgba8(self.recolor_image(&img.to_rgba8()));
+        // Formats without an alpha channel (jpeg) can't be written from rgba
+        let recolored = if has_alpha {
+            recolored
+        } else {
+            DynamicImage::ImageRgb8(recolored.to_rgb8())
+        };
+        recolored.save(output)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut file = File::create(path)?;
+        let json_str = serde_json::to_string_pretty(self)?;
+        file.write_all(json_str.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        let map = serde_json::from_str(&json)?;
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recolor_keeps_alpha_and_unmapped() {
+        let map = PaletteMap {
+            map_name: "test".to_string(),
+            entries: vec![(Rgb([255, 0, 0]), Rgb([0, 0, 255]))],
+        };
+        let mut img = RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, image::Rgba([255, 0, 0, 128]));
+        img.put_pixel(1, 0, image::Rgba([10, 20, 30, 255]));
+        let out = map.recolor_image(&img);
+        assert_eq!(out.get_pixel(0, 0), &image::Rgba([0, 0, 255, 128]));
+        assert_eq!(out.get_pixel(1, 0), &image::Rgba([10, 20, 30, 255]));
+    }
+
+    #[test]
+    fn recolor_dir_skips_broken_files() {
+        let dir = std::env::temp_dir().join(format!("palette_map_{}", std::process::id()));
+        let (input, output) = (dir.join("in"), dir.join("out"));
+        fs::create_dir_all(&input).unwrap();
+        RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]))
+            .save(input.join("a.png"))
+            .unwrap();
+        fs::write(input.join("broken.png"), b"not a png").unwrap();
+        RgbaImage::new(1, 1).save(input.join("c.png")).unwrap();
+
+        let map = PaletteMap {
+            map_name: "test".to_string(),
+            entries: vec![(Rgb([255, 0, 0]), Rgb([0, 0, 255]))],
+        };
+        let report = map.recolor_dir(&input, &output).unwrap();
+        assert_eq!(report.written, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, input.join("broken.png"));
+        let recolored = image::open(output.join("a.png")).unwrap().to_rgba8();
+        assert_eq!(recolored.get_pixel(0, 0), &image::Rgba([0, 0, 255, 255]));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,103 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    color::Rgb,
+    eyedropper::{Eyedropper, SampleSize},
+};
+
+/// Samples colors evenly along a polyline drawn over the source image.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct PathSampler {
+    /// Path vertices in source image pixel coordinates.
+    pub points: Vec<(f32, f32)>,
+    pub sample_count: usize,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub colors: Vec<Rgb<u8>>,
+}
+
+impl Default for PathSampler {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            sample_count: 8,
+            colors: Vec::new(),
+        }
+    }
+}
+
+impl PathSampler {
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.colors.clear();
+    }
+
+    /// `sample_count` positions spaced evenly by arc length, including both ends.
+    pub fn sample_positions(&self) -> Vec<(f32, f32)> {
+        let n = self.sample_count;
+        if self.points.is_empty() || n == 0 {
+            return Vec::new();
+        }
+        let lengths: Vec<f32> = self
+            .points
+            .windows(2)
+            .map(|w| f32::hypot(w[1].0 - w[0].0, w[1].1 - w[0].1))
+            .collect();
+        let total: f32 = lengths.iter().sum();
+        if n == 1 || total == 0. {
+            return vec![self.points[0]; n.min(1)];
+        }
+
+        let mut positions = Vec::with_capacity(n);
+        let mut segment = 0;
+        let mut segment_start = 0.;
+        for i in 0..n {
+            let target = total * i as f32 / (n - 1) as f32;
+            while segment < lengths.len() - 1 && segment_start + lengths[segment] < target {
+                segment_start += lengths[segment];
+                segment += 1;
+            }
+            let (a, b) = (self.points[segment], self.points[segment + 1]);
+            let t = if lengths[segment] > 0. {
+                ((target - segment_start) / lengths[segment]).clamp(0., 1.)
+            } else {
+                0.
+            };
+            positions.push((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+        }
+        positions
+    }
+
+    /// Refreshes `colors` from the image, in path order.
+    pub fn sample(&mut self, img: &DynamicImage, size: SampleSize) {
+        let (width, height) = (img.width(), img.height());
+        self.colors = self
+            .sample_positions()
+            .into_iter()
+            .filter_map(|(x, y)| {
+                let x = (x.max(0.) as u32).min(width.saturating_sub(1));
+                let y = (y.max(0.) as u32).min(height.saturating_sub(1));
+                Eyedropper::sample(img, x, y, size)
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_even_along_polyline() {
+        let sampler = PathSampler {
+            points: vec![(0., 0.), (10., 0.), (10., 10.)],
+            sample_count: 5,
+            colors: Vec::new(),
+        };
+        let positions = sampler.sample_positions();
+        assert_eq!(
+            positions,
+            vec![(0., 0.), (5., 0.), (10., 0.), (10., 5.), (10., 10.)]
+        );
+    }
+}
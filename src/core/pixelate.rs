@@ -0,0 +1,181 @@
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::color::{ColorUtil, Rgb};
+
+/// How the color of a block is picked from the pixels it covers.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BlockMode {
+    #[default]
+    Mode,
+    Median,
+    Average,
+}
+
+/// Target grid, either as an explicit size or as a block size in source pixels.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PixelGrid {
+    Size(u32, u32),
+    BlockSize(u32),
+}
+
+impl Default for PixelGrid {
+    fn default() -> Self {
+        PixelGrid::Size(64, 64)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct PixelateSettings {
+    pub grid: PixelGrid,
+    pub block_mode: BlockMode,
+    /// Nearest-neighbor upscale factor used for display and export.
+    pub scale: u32,
+}
+
+impl Default for PixelateSettings {
+    fn default() -> Self {
+        Self {
+            grid: PixelGrid::default(),
+            block_mode: BlockMode::default(),
+            scale: 8,
+        }
+    }
+}
+
+pub(crate) struct Pixelate {}
+
+impl Pixelate {
+    /// Downsamples `img` onto the settings grid and snaps every block to the
+    /// nearest color in `palette`. An empty palette keeps the block colors.
+    pub fn pixelate(
+        img: &DynamicImage,
+        palette: &[Rgb<u8>],
+        settings: &PixelateSettings,
+    ) -> RgbaImage {
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let (grid_w, grid_h) = Self::grid_size(width, height, settings.grid);
+        let mut out = RgbaImage::new(grid_w, grid_h);
+        for gy in 0..grid_h {
+            let y0 = gy * height / grid_h;
+            let y1 = ((gy + 1) * height / grid_h).max(y0 + 1);
+            for gx in 0..grid_w {
+                let x0 = gx * width / grid_w;
+                let x1 = ((gx + 1) * width / grid_w).max(x0 + 1);
+                let mut block = Vec::new();
+                let mut alpha_sum = 0u32;
+                for y in y0..y1.min(height) {
+                    for x in x0..x1.min(width) {
+                        let p = rgba.get_pixel(x, y);
+                        alpha_sum += p[3] as u32;
+                        // Fully transparent pixels don't contribute a color
+                        if p[3] > 0 {
+                            block.push(Rgb([p[0], p[1], p[2]]));
+                        }
+                    }
+                }
+                if block.is_empty() {
+                    out.put_pixel(gx, gy, Rgba([0, 0, 0, 0]));
+                    continue;
+                }
+                let pixel_count = (x1.min(width) - x0) * (y1.min(height) - y0);
+                let c = Self::block_color(&block, settings.block_mode);
+                let c = ColorUtil::nearest_color(c, palette);
+                let a = (alpha_sum / pixel_count) as u8;
+                out.put_pixel(gx, gy, Rgba([c[0], c[1], c[2], a]));
+            }
+        }
+        out
+    }
+
+    pub fn upscale(img: &RgbaImage, scale: u32) -> RgbaImage {
+        let scale = scale.max(1);
+        imageops::resize(
+            img,
+            img.width() * scale,
+            img.height() * scale,
+            imageops::FilterType::Nearest,
+        )
+    }
+
+    fn grid_size(width: u32, height: u32, grid: PixelGrid) -> (u32, u32) {
+        let (w, h) = match grid {
+            PixelGrid::Size(w, h) => (w, h),
+            PixelGrid::BlockSize(size) => {
+                let size = size.max(1);
+                (width.div_ceil(size), height.div_ceil(size))
+            }
+        };
+        (w.clamp(1, width.max(1)), h.clamp(1, height.max(1)))
+    }
+
+    fn block_color(block: &[Rgb<u8>], mode: BlockMode) -> Rgb<u8> {
+        match mode {
+            BlockMode::Mode => {
+                let mut counts = HashMap::<Rgb<u8>, usize>::new();
+                for c in block.iter() {
+                    *counts.entry(*c).or_insert(0) += 1;
+                }
+                // Ties resolve to the first color seen so the result is stable
+                let mut best = block[0];
+                let mut best_count = 0;
+                for c in block.iter() {
+                    let count = counts[c];
+                    if count > best_count {
+                        best = *c;
+                        best_count = count;
+                    }
+                }
+                best
+            }
+            BlockMode::Median => {
+                let mid = block.len() / 2;
+                let channel = |i: usize| {
+                    let mut values: Vec<u8> = block.iter().map(|c| c[i]).collect();
+                    values.sort_unstable();
+                    values[mid]
+                };
+                Rgb([channel(0), channel(1), channel(2)])
+            }
+            BlockMode::Average => {
+                let mut sum = [0u32; 3];
+                for c in block.iter() {
+                    sum[0] += c[0] as u32;
+                    sum[1] += c[1] as u32;
+                    sum[2] += c[2] as u32;
+                }
+                let n = block.len() as u32;
+                Rgb([(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixelate_snaps_blocks_to_palette() {
+        let mut img = RgbaImage::new(4, 2);
+        for (x, _, p) in img.enumerate_pixels_mut() {
+            *p = if x < 2 {
+                Rgba([250, 10, 10, 255])
+            } else {
+                Rgba([10, 10, 240, 255])
+            };
+        }
+        let settings = PixelateSettings {
+            grid: PixelGrid::BlockSize(2),
+            block_mode: BlockMode::Average,
+            scale: 1,
+        };
+        let palette = vec![Rgb([255, 0, 0]), Rgb([0, 0, 255])];
+        let out = Pixelate::pixelate(&DynamicImage::ImageRgba8(img), &palette, &settings);
+        assert_eq!(out.dimensions(), (2, 1));
+        assert_eq!(out.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(out.get_pixel(1, 0), &Rgba([0, 0, 255, 255]));
+    }
+}
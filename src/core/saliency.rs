@@ -0,0 +1,262 @@
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::color::{Lab, Rgb};
+
+/// Longest side of the grid saliency is estimated on.
+const MAX_WORKING_SIZE: u32 = 256;
+/// Fixed point scale for weighted counts, so they fit the palette's `usize` counts.
+const COUNT_SCALE: f32 = 100.;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct SaliencySettings {
+    /// Relative weights of the three cues, 0-1 each.
+    pub center_bias: f32,
+    pub local_contrast: f32,
+    pub edge_density: f32,
+    /// How many times more the most salient pixel counts than the least.
+    pub strength: f32,
+    pub use_mask: bool,
+    /// Mask brush radius in source pixels.
+    pub brush_radius: f32,
+}
+
+impl Default for SaliencySettings {
+    fn default() -> Self {
+        Self {
+            center_bias: 0.5,
+            local_contrast: 1.,
+            edge_density: 0.5,
+            strength: 8.,
+            use_mask: true,
+            brush_radius: 12.,
+        }
+    }
+}
+
+/// What a mask brush stroke does to the weights under it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MaskBrush {
+    #[default]
+    Boost,
+    Ignore,
+    Erase,
+}
+
+impl MaskBrush {
+    fn value(&self) -> f32 {
+        match self {
+            MaskBrush::Boost => 4.,
+            MaskBrush::Ignore => 0.,
+            MaskBrush::Erase => 1.,
+        }
+    }
+}
+
+/// Per-pixel weight multipliers on a grid covering the source image.
+#[derive(Clone, Debug)]
+pub(crate) struct WeightGrid {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl WeightGrid {
+    pub fn new(width: u32, height: u32, value: f32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![value; (width * height) as usize],
+        }
+    }
+
+    /// Grid of the working size used for `img`, filled with `value`.
+    pub fn for_image(img: &DynamicImage, value: f32) -> Self {
+        let (width, height) = Saliency::working_size(img.width(), img.height());
+        Self::new(width, height, value)
+    }
+
+    /// Value at the grid cell covering source pixel (x, y) of an image `size`.
+    fn at_source(&self, x: u32, y: u32, size: (u32, u32)) -> f32 {
+        let gx = (x as u64 * self.width as u64 / size.0.max(1) as u64) as u32;
+        let gy = (y as u64 * self.height as u64 / size.1.max(1) as u64) as u32;
+        self.values[(gy.min(self.height - 1) * self.width + gx.min(self.width - 1)) as usize]
+    }
+
+    /// Paints a disc at source image coordinates.
+    pub fn paint(&mut self, pos: (f32, f32), radius: f32, size: (u32, u32), brush: MaskBrush) {
+        let scale = self.width as f32 / size.0.max(1) as f32;
+        let (cx, cy, r) = (pos.0 * scale, pos.1 * scale, (radius * scale).max(0.5));
+        let min_x = (cx - r).floor().max(0.) as u32;
+        let min_y = (cy - r).floor().max(0.) as u32;
+        let max_x = ((cx + r).ceil() as u32).min(self.width);
+        let max_y = ((cy + r).ceil() as u32).min(self.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                if f32::hypot(x as f32 + 0.5 - cx, y as f32 + 0.5 - cy) <= r {
+                    self.values[(y * self.width + x) as usize] = brush.value();
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct Saliency {}
+
+impl Saliency {
+    fn working_size(width: u32, height: u32) -> (u32, u32) {
+        let longest = width.max(height).max(1);
+        if longest <= MAX_WORKING_SIZE {
+            return (width.max(1), height.max(1));
+        }
+        let scale = MAX_WORKING_SIZE as f32 / longest as f32;
+        (
+            ((width as f32 * scale).round() as u32).max(1),
+            ((height as f32 * scale).round() as u32).max(1),
+        )
+    }
+
+    /// Histogram weights: 1 for the least salient pixels up to `1 + strength`,
+    /// multiplied by the painted mask when enabled.
+    pub fn weights(
+        img: &DynamicImage,
+        settings: &SaliencySettings,
+        mask: Option<&WeightGrid>,
+    ) -> WeightGrid {
+        let (width, height) = Self::working_size(img.width(), img.height());
+        let small = img
+            .resize_exact(width, height, imageops::FilterType::Triangle)
+            .to_rgb8();
+        let labs: Vec<Lab> = small.pixels().map(|p| Lab::from_rgb(Rgb(p.0))).collect();
+        let (w, h) = (width as usize, height as usize);
+
+        let center = Self::normalize(
+            (0..w * h)
+                .map(|i| {
+                    let dx = ((i % w) as f32 + 0.5) / w as f32 - 0.5;
+                    let dy = ((i / w) as f32 + 0.5) / h as f32 - 0.5;
+                    (-(dx * dx + dy * dy) / (2. * 0.25 * 0.25)).exp()
+                })
+                .collect(),
+        );
+
+        // Color difference from the blurred surroundings
+        let blurred = imageops::blur(&small, (w.max(h) as f32 / 32.).max(1.));
+        let contrast = Self::normalize(
+            blurred
+                .pixels()
+                .zip(&labs)
+                .map(|(p, lab)| lab.delta_e(&Lab::from_rgb(Rgb(p.0))))
+                .collect(),
+        );
+
+        // Sobel magnitude on lightness, blurred into a density
+        let mut edges = RgbaImage::new(width, height);
+        let l = |x: usize, y: usize| labs[y.min(h - 1) * w + x.min(w - 1)].l;
+        for y in 0..h {
+            for x in 0..w {
+                let (xm, ym) = (x.saturating_sub(1), y.saturating_sub(1));
+                let gx = l(x + 1, ym) + 2. * l(x + 1, y) + l(x + 1, y + 1)
+                    - l(xm, ym)
+                    - 2. * l(xm, y)
+                    - l(xm, y + 1);
+                let gy = l(xm, y + 1) + 2. * l(x, y + 1) + l(x + 1, y + 1)
+                    - l(xm, ym)
+                    - 2. * l(x, ym)
+                    - l(x + 1, ym);
+                let v = (f32::hypot(gx, gy) / 4.).min(255.) as u8;
+                edges.put_pixel(x as u32, y as u32, Rgba([v, v, v, 255]));
+            }
+        }
+        let edges = imageops::blur(&edges, (w.max(h) as f32 / 64.).max(1.));
+        let edges = Self::normalize(edges.pixels().map(|p| p[0] as f32).collect());
+
+        let total =
+            (settings.center_bias + settings.local_contrast + settings.edge_density).max(0.001);
+        let values = (0..w * h)
+            .map(|i| {
+                let saliency = (settings.center_bias * center[i]
+                    + settings.local_contrast * contrast[i]
+                    + settings.edge_density * edges[i])
+                    / total;
+                let painted = match mask {
+                    Some(mask) if settings.use_mask => mask.values.get(i).copied().unwrap_or(1.),
+                    _ => 1.,
+                };
+                (1. + settings.strength * saliency) * painted
+            })
+            .collect();
+        WeightGrid {
+            width,
+            height,
+            values,
+        }
+    }
+
+    fn normalize(values: Vec<f32>) -> Vec<f32> {
+        let max = values.iter().copied().fold(0., f32::max);
+        if max <= 0. {
+            return values;
+        }
+        values.into_iter().map(|v| v / max).collect()
+    }
+
+    /// Like `Palette::extract_from_image`, but every pixel adds its weight
+    /// instead of 1. Counts are in hundredths of a pixel.
+    pub fn weighted_histogram(img: &DynamicImage, weights: &WeightGrid) -> HashMap<Rgb<u8>, usize> {
+        let size = img.dimensions();
+        let mut sums = HashMap::<Rgb<u8>, f32>::new();
+        for (x, y, p) in img.to_rgb8().enumerate_pixels() {
+            *sums.entry(Rgb(p.0)).or_insert(0.) += weights.at_source(x, y, size);
+        }
+        sums.into_iter()
+            .map(|(c, w)| (c, (w * COUNT_SCALE).round() as usize))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// Weights as a translucent overlay, transparent at weight 1 and warmer
+    /// the more a pixel counts. Ignored areas are darkened.
+    pub fn overlay(weights: &WeightGrid, strength: f32) -> RgbaImage {
+        let max = (1. + strength.max(0.)) * MaskBrush::Boost.value();
+        RgbaImage::from_fn(weights.width, weights.height, |x, y| {
+            let v = weights.values[(y * weights.width + x) as usize];
+            if v < 1. {
+                Rgba([0, 0, 0, ((1. - v) * 200.) as u8])
+            } else {
+                let t = ((v - 1.) / (max - 1.)).sqrt().clamp(0., 1.);
+                Rgba([255, (200. * (1. - t)) as u8, 0, (t * 180.) as u8])
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_contrasting_spot_gains_weight() {
+        // A 4x4 red "eye" in a 64x64 grey field
+        let img = RgbaImage::from_fn(64, 64, |x, y| {
+            if (30..34).contains(&x) && (30..34).contains(&y) {
+                Rgba([220, 20, 20, 255])
+            } else {
+                Rgba([120, 120, 120, 255])
+            }
+        });
+        let img = DynamicImage::ImageRgba8(img);
+        let weights = Saliency::weights(&img, &SaliencySettings::default(), None);
+        let counts = Saliency::weighted_histogram(&img, &weights);
+        let red = counts[&Rgb([220, 20, 20])] as f32 / 16.;
+        let grey = counts[&Rgb([120, 120, 120])] as f32 / (64. * 64. - 16.);
+        assert!(red > grey * 3.);
+
+        let mut mask = WeightGrid::for_image(&img, 1.);
+        mask.paint((32., 32.), 3., (64, 64), MaskBrush::Ignore);
+        let weights = Saliency::weights(&img, &SaliencySettings::default(), Some(&mask));
+        let counts = Saliency::weighted_histogram(&img, &weights);
+        assert!(!counts.contains_key(&Rgb([220, 20, 20])));
+    }
+}
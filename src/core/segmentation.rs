@@ -0,0 +1,273 @@
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::core::{
+    color::{Lab, Rgb},
+    palette::Palette,
+};
+
+/// Longest side of the working image, SLIC cost grows with the pixel count.
+const MAX_WORKING_SIZE: u32 = 400;
+const ITERATIONS: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct SegmentSettings {
+    /// Approximate number of superpixels.
+    pub segment_count: u32,
+    /// Higher values give more regular, less color-faithful segments.
+    pub compactness: f32,
+    pub colors_per_segment: usize,
+}
+
+impl Default for SegmentSettings {
+    fn default() -> Self {
+        Self {
+            segment_count: 48,
+            compactness: 10.,
+            colors_per_segment: 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Segment {
+    pub pixel_count: usize,
+    pub palette: Vec<Rgb<u8>>,
+}
+
+/// SLIC superpixels over a downscaled copy of the source image.
+pub(crate) struct Segmentation {
+    pub width: u32,
+    pub height: u32,
+    /// Segment index per working image pixel, row major.
+    labels: Vec<usize>,
+    pub segments: Vec<Segment>,
+    /// Source width / working width.
+    scale: f32,
+}
+
+impl Segmentation {
+    pub fn slic(img: &DynamicImage, settings: &SegmentSettings) -> Self {
+        let (src_w, src_h) = (img.width(), img.height());
+        // Nearest keeps real source colors for the sub-palettes
+        let working = if src_w.max(src_h) > MAX_WORKING_SIZE {
+            img.resize(
+                MAX_WORKING_SIZE,
+                MAX_WORKING_SIZE,
+                imageops::FilterType::Nearest,
+            )
+        } else {
+            img.clone()
+        };
+        let rgb = working.to_rgb8();
+        let (width, height) = rgb.dimensions();
+        let pixels: Vec<Rgb<u8>> = rgb.pixels().map(|p| Rgb(p.0)).collect();
+        let labs: Vec<Lab> = pixels.iter().map(|c| Lab::from_rgb(*c)).collect();
+
+        let labels = Self::cluster(&labs, width, height, settings);
+        let labels = Self::enforce_connectivity(&labels, width, height, settings.segment_count);
+        let segment_count = labels.iter().max().map_or(0, |m| m + 1);
+
+        let mut histograms = vec![HashMap::<Rgb<u8>, usize>::new(); segment_count];
+        for (label, c) in labels.iter().zip(&pixels) {
+            *histograms[*label].entry(*c).or_insert(0) += 1;
+        }
+        let segments = histograms
+            .into_iter()
+            .map(|histogram| {
+                let pixel_count = histogram.values().sum();
+                let mut entries: Vec<(Rgb<u8>, usize)> = histogram.into_iter().collect();
+                entries.sort_by_key(|e| std::cmp::Reverse(e.1));
+                let (palette, _) = Palette::get_top_colors(entries, settings.colors_per_segment);
+                Segment {
+                    pixel_count,
+                    palette,
+                }
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            labels,
+            segments,
+            scale: src_w as f32 / width as f32,
+        }
+    }
+
+    /// Assigns every pixel to one of roughly `segment_count` cluster centers
+    /// by combined Lab and spatial distance.
+    fn cluster(labs: &[Lab], width: u32, height: u32, settings: &SegmentSettings) -> Vec<usize> {
+        let (w, h) = (width as usize, height as usize);
+        let step = ((w * h) as f32 / settings.segment_count.max(1) as f32)
+            .sqrt()
+            .max(1.);
+        // [l, a, b, x, y]
+        let mut centers: Vec<[f32; 5]> = Vec::new();
+        let mut y = step / 2.;
+        while y < h as f32 {
+            let mut x = step / 2.;
+            while x < w as f32 {
+                let lab = &labs[y as usize * w + x as usize];
+                centers.push([lab.l, lab.a, lab.b, x, y]);
+                x += step;
+            }
+            y += step;
+        }
+
+        let spatial_weight = (settings.compactness / step).powi(2);
+        let mut labels = vec![0; w * h];
+        let mut distances = vec![f32::MAX; w * h];
+        let window = (2. * step).ceil() as i64;
+        for _ in 0..ITERATIONS {
+            distances.fill(f32::MAX);
+            for (k, center) in centers.iter().enumerate() {
+                let (cx, cy) = (center[3] as i64, center[4] as i64);
+                for py in (cy - window).max(0)..(cy + window).min(h as i64) {
+                    for px in (cx - window).max(0)..(cx + window).min(w as i64) {
+                        let i = py as usize * w + px as usize;
+                        let lab = &labs[i];
+                        let dc = (lab.l - center[0]).powi(2)
+                            + (lab.a - center[1]).powi(2)
+                            + (lab.b - center[2]).powi(2);
+                        let ds = (px as f32 - center[3]).powi(2) + (py as f32 - center[4]).powi(2);
+                        let d = dc + ds * spatial_weight;
+                        if d < distances[i] {
+                            distances[i] = d;
+                            labels[i] = k;
+                        }
+                    }
+                }
+            }
+
+            let mut sums = vec![[0f32; 6]; centers.len()];
+            for (i, label) in labels.iter().enumerate() {
+                let lab = &labs[i];
+                let sum = &mut sums[*label];
+                sum[0] += lab.l;
+                sum[1] += lab.a;
+                sum[2] += lab.b;
+                sum[3] += (i % w) as f32;
+                sum[4] += (i / w) as f32;
+                sum[5] += 1.;
+            }
+            for (center, sum) in centers.iter_mut().zip(&sums) {
+                if sum[5] > 0. {
+                    for c in 0..5 {
+                        center[c] = sum[c] / sum[5];
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    /// Relabels connected components and folds fragments smaller than a
+    /// quarter of the expected segment size into a neighbor.
+    fn enforce_connectivity(
+        labels: &[usize],
+        width: u32,
+        height: u32,
+        segment_count: u32,
+    ) -> Vec<usize> {
+        let (w, h) = (width as usize, height as usize);
+        let min_size = (w * h) / (segment_count.max(1) as usize * 4);
+        let mut out = vec![usize::MAX; w * h];
+        let mut next = 0;
+        let mut queue = VecDeque::new();
+        let mut component = Vec::new();
+        for start in 0..w * h {
+            if out[start] != usize::MAX {
+                continue;
+            }
+            let mut adjacent = None;
+            component.clear();
+            queue.push_back(start);
+            out[start] = next;
+            while let Some(i) = queue.pop_front() {
+                component.push(i);
+                let (x, y) = (i % w, i / w);
+                let neighbors = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < w).then(|| i + 1),
+                    (y > 0).then(|| i - w),
+                    (y + 1 < h).then(|| i + w),
+                ];
+                for n in neighbors.into_iter().flatten() {
+                    if out[n] == usize::MAX && labels[n] == labels[start] {
+                        out[n] = next;
+                        queue.push_back(n);
+                    } else if out[n] != usize::MAX && out[n] != next {
+                        adjacent = Some(out[n]);
+                    }
+                }
+            }
+            match adjacent {
+                Some(label) if component.len() < min_size => {
+                    for i in &component {
+                        out[*i] = label;
+                    }
+                }
+                _ => next += 1,
+            }
+        }
+        out
+    }
+
+    /// Segment under a source image pixel.
+    pub fn segment_at(&self, x: u32, y: u32) -> Option<usize> {
+        let x = (x as f32 / self.scale) as u32;
+        let y = (y as f32 / self.scale) as u32;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.labels[(y * self.width + x) as usize])
+    }
+
+    /// Segment boundaries, with `selected` segments tinted, at working resolution.
+    pub fn overlay(&self, selected: &[usize], hovered: Option<usize>) -> RgbaImage {
+        let (w, h) = (self.width as usize, self.height as usize);
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let i = y as usize * w + x as usize;
+            let label = self.labels[i];
+            let edge = (x as usize + 1 < w && self.labels[i + 1] != label)
+                || (y as usize + 1 < h && self.labels[i + w] != label);
+            if edge {
+                Rgba([255, 255, 255, 200])
+            } else if selected.contains(&label) {
+                Rgba([255, 210, 0, 90])
+            } else if hovered == Some(label) {
+                Rgba([255, 255, 255, 60])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_two_color_halves() {
+        let img = RgbaImage::from_fn(40, 20, |x, _| {
+            if x < 20 {
+                Rgba([200, 20, 20, 255])
+            } else {
+                Rgba([20, 20, 200, 255])
+            }
+        });
+        let settings = SegmentSettings {
+            segment_count: 8,
+            ..Default::default()
+        };
+        let seg = Segmentation::slic(&DynamicImage::ImageRgba8(img), &settings);
+        let left = seg.segment_at(2, 10).unwrap();
+        let right = seg.segment_at(37, 10).unwrap();
+        assert_ne!(left, right);
+        assert_eq!(seg.segments[left].palette, vec![Rgb([200, 20, 20])]);
+        assert_eq!(seg.segments[right].palette, vec![Rgb([20, 20, 200])]);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::core::{
+    color::{ColorUtil, Rgb},
+    color_detail::ColorDetail,
+    color_vision::ColorVision,
+    css_color::ColorFormat,
+    palette::Palette,
+    swatch_sheet::xml_escape,
+};
+
+const STYLE: &str = "
+body { font-family: system-ui, -apple-system, 'Segoe UI', sans-serif; margin: 2rem; color: #222; background: #fafafa; }
+h1 { margin-bottom: .5rem; }
+.strip { display: flex; height: 3rem; border-radius: 6px; overflow: hidden; margin-bottom: 2rem; }
+.strip div { flex: 1; }
+.color { display: grid; grid-template-columns: 12rem 1fr; gap: 1.5rem; background: #fff; border-radius: 8px; padding: 1rem; margin-bottom: 1.5rem; box-shadow: 0 1px 3px rgba(0,0,0,.15); }
+.swatch { border-radius: 6px; min-height: 12rem; display: flex; align-items: flex-end; padding: .75rem; font-weight: 600; }
+h2 { margin: 0 0 .5rem; font-size: 1.2rem; }
+h3 { margin: 1rem 0 .4rem; font-size: .8rem; text-transform: uppercase; letter-spacing: .05em; color: #666; }
+table { border-collapse: collapse; font-size: .9rem; }
+td { padding: .15rem .75rem .15rem 0; vertical-align: middle; }
+code { font-family: ui-monospace, Menlo, Consolas, monospace; }
+.chips { display: flex; flex-wrap: wrap; gap: .75rem; }
+.chip { font-size: .8rem; text-align: center; }
+.chip div { width: 4rem; height: 2.5rem; border-radius: 4px; margin-bottom: .25rem; border: 1px solid rgba(0,0,0,.1); }
+.sample { display: inline-block; width: 2.5rem; text-align: center; border-radius: 4px; font-weight: 600; }
+.fail { color: #b00020; }
+";
+
+/// Self-contained HTML page documenting a palette, for sharing with people
+/// who do not have the app.
+pub(crate) struct StyleGuide {}
+
+impl StyleGuide {
+    pub fn render_html(palette: &Palette) -> String {
+        let title = xml_escape(&palette.palette_name);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{} colors</p>\n<div class=\"strip\">",
+            palette.top_rgb.len()
+        );
+        for c in &palette.top_rgb {
+            html.push_str(&format!("<div style=\"background:{}\"></div>", hex(*c)));
+        }
+        html.push_str("</div>\n");
+        for c in &palette.top_rgb {
+            html.push_str(&Self::color_section(palette, *c));
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn color_section(palette: &Palette, c: Rgb<u8>) -> String {
+        let name = xml_escape(&palette.color_name(&c));
+        let text = ColorUtil::contrast_text(&c);
+        let text = Rgb([text.r(), text.g(), text.b()]);
+        let mut html = format!(
+            "<section class=\"color\">\n<div class=\"swatch\" style=\"background:{};color:{}\">{name}</div>\n<div>\n<h2>{name}</h2>\n",
+            hex(c),
+            hex(text)
+        );
+        if let Some(role) = palette.role(&c) {
+            html.push_str(&format!("<p>Role: {}</p>\n", xml_escape(role)));
+        }
+
+        html.push_str("<h3>Formats</h3>\n<table>\n");
+        for format in ColorFormat::ALL {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td><code>{}</code></td></tr>\n",
+                format.label(),
+                xml_escape(&format.format(c))
+            ));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h3>Contrast (WCAG 2)</h3>\n<table>\n");
+        for (label, other) in [("Black", Rgb([0, 0, 0])), ("White", Rgb([255, 255, 255]))] {
+            let ratio = ColorUtil::contrast_ratio(c, other);
+            let rating = wcag_rating(ratio);
+            let class = if ratio < 3. { " class=\"fail\"" } else { "" };
+            html.push_str(&format!(
+                "<tr><td><span class=\"sample\" style=\"background:{};color:{}\">Aa</span></td><td>{label} text</td><td>{ratio:.2}:1</td><td{class}>{rating}</td></tr>\n",
+                hex(c),
+                hex(other)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h3>Color vision</h3>\n<div class=\"chips\">\n");
+        for vision in ColorVision::ALL {
+            html.push_str(&chip(vision.simulate(c), vision.label()));
+        }
+        html.push_str("</div>\n");
+
+        let detail = ColorDetail::new(c);
+        html.push_str("<h3>Relations</h3>\n<div class=\"chips\">\n");
+        html.push_str(&chip(detail.complement, "Complement"));
+        html.push_str(&chip(detail.split_complement.0, "Split complement"));
+        html.push_str(&chip(detail.split_complement.1, "Split complement"));
+        html.push_str("</div>\n</div>\n</section>\n");
+        html
+    }
+}
+
+fn chip(c: Rgb<u8>, label: &str) -> String {
+    format!(
+        "<div class=\"chip\"><div style=\"background:{0}\"></div>{label}<br><code>{0}</code></div>\n",
+        hex(c)
+    )
+}
+
+fn hex(c: Rgb<u8>) -> String {
+    ColorUtil::rgb_to_hex(c).to_lowercase()
+}
+
+fn wcag_rating(ratio: f32) -> &'static str {
+    if ratio >= 7. {
+        "AAA"
+    } else if ratio >= 4.5 {
+        "AA"
+    } else if ratio >= 3. {
+        "AA large text"
+    } else {
+        "Fail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_contained_page() {
+        let palette = Palette::from_colors(
+            "Salt & Pepper",
+            vec![
+                (Rgb([255, 255, 255]), Some("Salt".to_string())),
+                (Rgb([119, 119, 119]), Some("<Pepper>".to_string())),
+            ],
+        );
+        let html = StyleGuide::render_html(&palette);
+        assert!(html.contains("<title>Salt &amp; Pepper</title>"));
+        assert!(html.contains("<h2>&lt;Pepper&gt;</h2>"));
+        assert!(html.contains("<td>Black text</td><td>21.00:1</td><td>AAA</td>"));
+        assert!(html.contains("<td>White text</td><td>4.48:1</td><td>AA large text</td>"));
+        assert!(!html.contains("src=") && !html.contains("href="));
+    }
+}
@@ -0,0 +1,474 @@
+use font_kit::font::Font;
+use image::RgbaImage;
+use raqote::{DrawOptions, DrawTarget, Point, SolidSource, Source};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core::{
+    color::{ColorUtil, Rgb},
+    palette::Palette,
+};
+
+/// egui's bundled proportional font, so labels look the same everywhere.
+const LABEL_FONT: &str = "Ubuntu-Light";
+const LINE_SPACING: f32 = 1.25;
+/// Rough Helvetica advance per em, for cutting labels in vector sheets.
+const AVERAGE_ADVANCE: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SheetPreset {
+    /// One row of large swatches.
+    Strip,
+    #[default]
+    Grid,
+    /// One pixel per color in a single row, as Lospec hosts palettes.
+    Lospec,
+}
+
+impl SheetPreset {
+    pub const ALL: [SheetPreset; 3] = [SheetPreset::Strip, SheetPreset::Grid, SheetPreset::Lospec];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SheetPreset::Strip => "Strip",
+            SheetPreset::Grid => "Grid",
+            SheetPreset::Lospec => "1px strip (Lospec)",
+        }
+    }
+}
+
+/// Layout options shared by every swatch sheet format.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct SheetSettings {
+    pub swatch_size: u32,
+    pub columns: usize,
+    /// Around the whole sheet.
+    pub margin: u32,
+    /// Between swatches.
+    pub gap: u32,
+    pub background: Rgb<u8>,
+    pub label_hex: bool,
+    pub label_rgb: bool,
+    pub label_name: bool,
+    #[serde(default)]
+    pub label_cmyk: bool,
+    pub font_size: f32,
+}
+
+impl Default for SheetSettings {
+    fn default() -> Self {
+        Self::preset(SheetPreset::default(), 6)
+    }
+}
+
+/// Pixel positions of everything on a sheet for `count` colors.
+pub(crate) struct SheetLayout {
+    pub width: u32,
+    pub height: u32,
+    /// Top left corner of each swatch, labels go below it.
+    pub cells: Vec<(u32, u32)>,
+}
+
+impl SheetSettings {
+    pub fn preset(preset: SheetPreset, count: usize) -> Self {
+        let base = Self {
+            swatch_size: 64,
+            columns: 6,
+            margin: 16,
+            gap: 16,
+            background: Rgb([255, 255, 255]),
+            label_hex: true,
+            label_rgb: false,
+            label_name: false,
+            label_cmyk: false,
+            font_size: 11.,
+        };
+        match preset {
+            SheetPreset::Grid => base,
+            SheetPreset::Strip => Self {
+                columns: count.max(1),
+                margin: 0,
+                gap: 0,
+                label_hex: false,
+                ..base
+            },
+            SheetPreset::Lospec => Self {
+                swatch_size: 1,
+                columns: count.max(1),
+                margin: 0,
+                gap: 0,
+                label_hex: false,
+                ..base
+            },
+        }
+    }
+
+    pub fn label_lines(&self) -> usize {
+        [
+            self.label_hex,
+            self.label_rgb,
+            self.label_cmyk,
+            self.label_name,
+        ]
+        .iter()
+        .filter(|l| **l)
+        .count()
+    }
+
+    pub fn line_height(&self) -> u32 {
+        (self.font_size * LINE_SPACING).ceil() as u32
+    }
+
+    /// Label text under `c`, one entry per enabled line.
+    pub fn labels(&self, palette: &Palette, c: &Rgb<u8>) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.label_hex {
+            lines.push(ColorUtil::rgb_to_hex(*c));
+        }
+        if self.label_rgb {
+            lines.push(format!("{} {} {}", c[0], c[1], c[2]));
+        }
+        if self.label_cmyk {
+            let [c, m, y, k] = ColorUtil::rgb_to_cmyk(*c).map(|v| (v * 100.).round());
+            lines.push(format!("{c} {m} {y} {k}"));
+        }
+        if self.label_name {
+            lines.push(palette.color_name(c));
+        }
+        lines
+    }
+
+    /// Labels contrast with the background they sit on.
+    fn text_color(&self) -> Rgb<u8> {
+        let text = ColorUtil::contrast_text(&self.background);
+        Rgb([text.r(), text.g(), text.b()])
+    }
+
+    /// Cuts `text` to about the swatch width, for formats without font metrics.
+    fn fit<'a>(&self, text: &'a str) -> &'a str {
+        let max = (self.swatch_size as f32 / (self.font_size * AVERAGE_ADVANCE)) as usize;
+        match text.char_indices().nth(max) {
+            Some((i, _)) => &text[..i],
+            None => text,
+        }
+    }
+
+    pub fn layout(&self, count: usize) -> SheetLayout {
+        let columns = self.columns.clamp(1, count.max(1)) as u32;
+        let rows = (count as u32).div_ceil(columns).max(1);
+        let label_height = match self.label_lines() as u32 {
+            0 => 0,
+            lines => lines * self.line_height() + self.gap.min(4),
+        };
+        let cell_height = self.swatch_size + label_height;
+        let cells = (0..count as u32)
+            .map(|i| {
+                (
+                    self.margin + (i % columns) * (self.swatch_size + self.gap),
+                    self.margin + (i / columns) * (cell_height + self.gap),
+                )
+            })
+            .collect();
+        SheetLayout {
+            width: 2 * self.margin + columns * self.swatch_size + (columns - 1) * self.gap,
+            height: 2 * self.margin + rows * cell_height + (rows - 1) * self.gap,
+            cells,
+        }
+    }
+}
+
+pub(crate) struct SwatchSheet {}
+
+impl SwatchSheet {
+    pub fn render_png(palette: &Palette, settings: &SheetSettings) -> RgbaImage {
+        let layout = settings.layout(palette.top_rgb.len());
+        let solid = |c: &Rgb<u8>| {
+            Source::Solid(SolidSource::from_unpremultiplied_argb(
+                0xff, c[0], c[1], c[2],
+            ))
+        };
+        let options = DrawOptions::new();
+        let mut dt = DrawTarget::new(layout.width as i32, layout.height as i32);
+        let (width, height) = (layout.width as f32, layout.height as f32);
+        dt.fill_rect(
+            0.,
+            0.,
+            width,
+            height,
+            &solid(&settings.background),
+            &options,
+        );
+
+        let text = solid(&settings.text_color());
+        let font = Self::font();
+        let size = settings.swatch_size as f32;
+        for (c, (x, y)) in palette.top_rgb.iter().zip(&layout.cells) {
+            dt.fill_rect(*x as f32, *y as f32, size, size, &solid(c), &options);
+            let Some(font) = &font else {
+                continue;
+            };
+            let mut line_y = y + settings.swatch_size + settings.gap.min(4);
+            for line in settings.labels(palette, c) {
+                Self::draw_text(&mut dt, font, &line, (*x, line_y), settings, &text);
+                line_y += settings.line_height();
+            }
+        }
+
+        // Everything is drawn over an opaque background, so the premultiplied
+        // ARGB pixels are plain RGB
+        let pixels = dt
+            .get_data()
+            .iter()
+            .flat_map(|p| {
+                let [_, r, g, b] = p.to_be_bytes();
+                [r, g, b, 255]
+            })
+            .collect();
+        RgbaImage::from_raw(layout.width, layout.height, pixels).unwrap_or_default()
+    }
+
+    /// Vector sheet with text labels, one `<rect>` per swatch.
+    pub fn render_svg(palette: &Palette, settings: &SheetSettings) -> String {
+        let layout = settings.layout(palette.top_rgb.len());
+        let (width, height) = (layout.width, layout.height);
+        let size = settings.swatch_size;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        svg.push_str(&format!(
+            "  <title>{}</title>\n  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            xml_escape(&palette.palette_name),
+            ColorUtil::rgb_to_hex(settings.background).to_lowercase()
+        ));
+        svg.push_str(&format!(
+            "  <g font-family=\"Helvetica, Arial, sans-serif\" font-size=\"{}\" fill=\"{}\">\n",
+            settings.font_size,
+            ColorUtil::rgb_to_hex(settings.text_color()).to_lowercase()
+        ));
+        for (c, (x, y)) in palette.top_rgb.iter().zip(&layout.cells) {
+            let name = xml_escape(&palette.color_name(c));
+            let hex = ColorUtil::rgb_to_hex(*c).to_lowercase();
+            svg.push_str(&format!(
+                "    <rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" fill=\"{hex}\"><title>{name}</title></rect>\n"
+            ));
+            let mut line_y = y + size + settings.gap.min(4);
+            for line in settings.labels(palette, c) {
+                let baseline = line_y as f32 + settings.font_size;
+                let text = xml_escape(settings.fit(&line));
+                svg.push_str(&format!(
+                    "    <text x=\"{x}\" y=\"{baseline}\">{text}</text>\n"
+                ));
+                line_y += settings.line_height();
+            }
+        }
+        svg.push_str("  </g>\n</svg>\n");
+        svg
+    }
+
+    /// Single page PDF, one point per pixel of the PNG layout. Labels use the
+    /// standard Helvetica font so nothing has to be embedded.
+    pub fn render_pdf(palette: &Palette, settings: &SheetSettings) -> Vec<u8> {
+        let layout = settings.layout(palette.top_rgb.len());
+        let height = layout.height as f32;
+        let size = settings.swatch_size as f32;
+        let fill = |c: &Rgb<u8>| {
+            let [r, g, b] = c.0.map(|v| v as f32 / 255.);
+            format!("{r:.3} {g:.3} {b:.3} rg\n")
+        };
+
+        let mut content = Vec::new();
+        content.extend(fill(&settings.background).as_bytes());
+        content.extend(format!("0 0 {} {height} re f\n", layout.width).as_bytes());
+        for (c, (x, y)) in palette.top_rgb.iter().zip(&layout.cells) {
+            // PDF measures y from the bottom of the page
+            let bottom = height - *y as f32 - size;
+            content.extend(fill(c).as_bytes());
+            content.extend(format!("{x} {bottom} {size} {size} re f\n").as_bytes());
+
+            let labels = settings.labels(palette, c);
+            if labels.is_empty() {
+                continue;
+            }
+            content.extend(fill(&settings.text_color()).as_bytes());
+            content.extend(format!("BT /F1 {} Tf\n", settings.font_size).as_bytes());
+            let mut line_y = (y + settings.swatch_size + settings.gap.min(4)) as f32;
+            for line in labels {
+                let baseline = height - line_y - settings.font_size;
+                content.extend(format!("1 0 0 1 {x} {baseline} Tm (").as_bytes());
+                content.extend(pdf_string(settings.fit(&line)));
+                content.extend(b") Tj\n");
+                line_y += settings.line_height() as f32;
+            }
+            content.extend(b"ET\n");
+        }
+
+        let objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+                layout.width, layout.height
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            [
+                format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(),
+                &content,
+                b"endstream",
+            ]
+            .concat(),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+
+    fn font() -> Option<Font> {
+        let fonts = egui::FontDefinitions::default();
+        let data = fonts.font_data.get(LABEL_FONT)?;
+        Font::from_bytes(Arc::new(data.font.to_vec()), 0).ok()
+    }
+
+    /// Draws `text` left aligned below `pos`, cut off at the swatch width.
+    fn draw_text(
+        dt: &mut DrawTarget,
+        font: &Font,
+        text: &str,
+        pos: (u32, u32),
+        settings: &SheetSettings,
+        color: &Source,
+    ) {
+        let metrics = font.metrics();
+        let scale = settings.font_size / metrics.units_per_em as f32;
+        let mut width = 0.;
+        let mut end = text.len();
+        for (i, ch) in text.char_indices() {
+            let advance = font
+                .glyph_for_char(ch)
+                .and_then(|id| font.advance(id).ok())
+                .map_or(0., |v| v.x() * scale);
+            if width + advance > settings.swatch_size as f32 {
+                end = i;
+                break;
+            }
+            width += advance;
+        }
+        let baseline = pos.1 as f32 + metrics.ascent * scale;
+        dt.draw_text(
+            font,
+            settings.font_size,
+            &text[..end],
+            Point::new(pos.0 as f32, baseline),
+            color,
+            &DrawOptions::new(),
+        );
+    }
+}
+
+/// Escapes text for XML or HTML content and attribute values.
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Latin-1 bytes for a literal string, other characters become `?`.
+fn pdf_string(s: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for ch in s.chars() {
+        match ch {
+            '(' | ')' | '\\' => bytes.extend([b'\\', ch as u8]),
+            ch if (ch as u32) < 256 => bytes.push(ch as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_fits_every_row() {
+        let palette =
+            Palette::from_colors("", (0..13u8).map(|i| (Rgb([i * 10, 0, 0]), None)).collect());
+        let settings = SheetSettings::default();
+        let layout = settings.layout(13);
+        let (x, y) = *layout.cells.last().unwrap();
+        assert_eq!(
+            (x, y),
+            (16, 16 + 2 * (64 + settings.line_height() + 4 + 16))
+        );
+        assert!(y + settings.swatch_size <= layout.height);
+
+        // The hex label below the first swatch is drawn
+        let img = SwatchSheet::render_png(&palette, &settings);
+        let label_y = 16 + 64 + 4;
+        let inked = (label_y..label_y + settings.line_height())
+            .flat_map(|y| (16..16 + 64).map(move |x| (x, y)))
+            .any(|(x, y)| img.get_pixel(x, y) != &image::Rgba([255, 255, 255, 255]));
+        assert!(inked);
+
+        let img =
+            SwatchSheet::render_png(&palette, &SheetSettings::preset(SheetPreset::Lospec, 13));
+        assert_eq!(img.dimensions(), (13, 1));
+        assert_eq!(img.get_pixel(12, 0), &image::Rgba([120, 0, 0, 255]));
+    }
+
+    #[test]
+    fn vector_sheets() {
+        let palette = Palette::from_colors(
+            "Tea & Cake",
+            vec![
+                (Rgb([255, 0, 0]), Some("Red (warm)".to_string())),
+                (Rgb([0, 128, 255]), None),
+            ],
+        );
+        let settings = SheetSettings {
+            label_cmyk: true,
+            label_name: true,
+            ..SheetSettings::default()
+        };
+
+        let svg = SwatchSheet::render_svg(&palette, &settings);
+        assert!(svg.contains("<title>Tea &amp; Cake</title>"));
+        assert!(
+            svg.contains("<rect x=\"16\" y=\"16\" width=\"64\" height=\"64\" fill=\"#ff0000\">")
+        );
+        assert!(svg.contains(">0 100 100 0</text>"));
+
+        let pdf = SwatchSheet::render_pdf(&palette, &settings);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4\n") && text.ends_with("%%EOF\n"));
+        assert!(text.contains("1.000 0.000 0.000 rg\n16 "));
+        assert!(text.contains("(Red \\(warm\\)) Tj"));
+        // Every xref entry points at its object
+        let xref = text.rfind("xref\n").unwrap();
+        for (i, entry) in text[xref..].lines().skip(3).take(5).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::color::{ColorUtil, Lab, Rgb};
+
+/// The eight ANSI color names, the bright variants follow in the same order.
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Lab hue angle of the sRGB primaries and secondaries, for the six chromatic
+/// ANSI slots in order.
+const ANSI_HUES: [f32; 6] = [40., 136., 103., 306., 328., 197.];
+
+pub const BACKGROUND: usize = 16;
+pub const FOREGROUND: usize = 17;
+pub const CURSOR: usize = 18;
+
+/// Terminal and editor theme formats.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TerminalFormat {
+    #[default]
+    Alacritty,
+    Kitty,
+    Xresources,
+    WindowsTerminal,
+    VsCode,
+}
+
+impl TerminalFormat {
+    pub const ALL: [TerminalFormat; 5] = [
+        TerminalFormat::Alacritty,
+        TerminalFormat::Kitty,
+        TerminalFormat::Xresources,
+        TerminalFormat::WindowsTerminal,
+        TerminalFormat::VsCode,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TerminalFormat::Alacritty => "Alacritty",
+            TerminalFormat::Kitty => "kitty",
+            TerminalFormat::Xresources => "Xresources",
+            TerminalFormat::WindowsTerminal => "Windows Terminal",
+            TerminalFormat::VsCode => "VS Code",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TerminalFormat::Alacritty => "toml",
+            TerminalFormat::Kitty => "conf",
+            TerminalFormat::Xresources => "Xresources",
+            TerminalFormat::WindowsTerminal => "json",
+            TerminalFormat::VsCode => "color-theme.json",
+        }
+    }
+}
+
+/// The 16 ANSI colors followed by background, foreground and cursor.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct TerminalTheme {
+    pub slots: [Rgb<u8>; 19],
+}
+
+impl TerminalTheme {
+    pub fn slot_name(i: usize) -> String {
+        match i {
+            0..8 => capitalize(ANSI_NAMES[i]),
+            8..16 => format!("Bright {}", ANSI_NAMES[i - 8]),
+            BACKGROUND => "Background".to_string(),
+            FOREGROUND => "Foreground".to_string(),
+            _ => "Cursor".to_string(),
+        }
+    }
+
+    /// Picks a palette color for every slot by hue and lightness, assuming a
+    /// dark background. Chromatic slots avoid greys, the bright ones lean lighter.
+    pub fn suggest(colors: &[Rgb<u8>]) -> Self {
+        let labs: Vec<(Rgb<u8>, Lab)> = colors.iter().map(|c| (*c, Lab::from_rgb(*c))).collect();
+        let pick = |cost: &dyn Fn(&Lab) -> f32| {
+            labs.iter()
+                .min_by(|a, b| cost(&a.1).total_cmp(&cost(&b.1)))
+                .map(|(c, _)| *c)
+                .unwrap_or(Rgb([0, 0, 0]))
+        };
+        let neutral = |l: f32| move |lab: &Lab| (lab.l - l).abs() / 100. + chroma(lab) / 50.;
+        let chromatic = |hue: f32, l: f32| {
+            move |lab: &Lab| {
+                let grey_penalty = if chroma(lab) < 15. { 2. } else { 0. };
+                hue_distance(hue_angle(lab), hue) / 60. + (lab.l - l).abs() / 50. + grey_penalty
+            }
+        };
+
+        let mut slots = [Rgb([0, 0, 0]); 19];
+        for (bright, l) in [(0, 55.), (8, 70.)] {
+            for (i, hue) in ANSI_HUES.iter().enumerate() {
+                slots[bright + i + 1] = pick(&chromatic(*hue, l));
+            }
+        }
+        slots[0] = pick(&neutral(10.));
+        slots[7] = pick(&neutral(80.));
+        slots[8] = pick(&neutral(45.));
+        slots[15] = pick(&neutral(100.));
+        slots[BACKGROUND] = pick(&|lab: &Lab| lab.l);
+        slots[FOREGROUND] = pick(&neutral(90.));
+        slots[CURSOR] = slots[FOREGROUND];
+        Self { slots }
+    }
+
+    pub fn export(&self, format: TerminalFormat, name: &str) -> String {
+        let hex = |i: usize| ColorUtil::rgb_to_hex(self.slots[i]).to_lowercase();
+        let mut out = String::new();
+        match format {
+            TerminalFormat::Alacritty => {
+                out.push_str(&format!("# {name}\n[colors.primary]\n"));
+                out.push_str(&format!("background = \"{}\"\n", hex(BACKGROUND)));
+                out.push_str(&format!("foreground = \"{}\"\n", hex(FOREGROUND)));
+                out.push_str(&format!(
+                    "\n[colors.cursor]\ntext = \"{}\"\ncursor = \"{}\"\n",
+                    hex(BACKGROUND),
+                    hex(CURSOR)
+                ));
+                for (table, offset) in [("normal", 0), ("bright", 8)] {
+                    out.push_str(&format!("\n[colors.{table}]\n"));
+                    for (i, ansi) in ANSI_NAMES.iter().enumerate() {
+                        out.push_str(&format!("{ansi} = \"{}\"\n", hex(offset + i)));
+                    }
+                }
+            }
+            TerminalFormat::Kitty => {
+                out.push_str(&format!("# {name}\n"));
+                out.push_str(&format!("background {}\n", hex(BACKGROUND)));
+                out.push_str(&format!("foreground {}\n", hex(FOREGROUND)));
+                out.push_str(&format!("cursor {}\n", hex(CURSOR)));
+                out.push_str(&format!("cursor_text_color {}\n", hex(BACKGROUND)));
+                out.push_str(&format!("selection_background {}\n", hex(8)));
+                out.push_str(&format!("selection_foreground {}\n", hex(FOREGROUND)));
+                for i in 0..16 {
+                    out.push_str(&format!("color{i} {}\n", hex(i)));
+                }
+            }
+            TerminalFormat::Xresources => {
+                out.push_str(&format!("! {name}\n"));
+                out.push_str(&format!("*.background: {}\n", hex(BACKGROUND)));
+                out.push_str(&format!("*.foreground: {}\n", hex(FOREGROUND)));
+                out.push_str(&format!("*.cursorColor: {}\n", hex(CURSOR)));
+                for i in 0..16 {
+                    out.push_str(&format!("*.color{i}: {}\n", hex(i)));
+                }
+            }
+            TerminalFormat::WindowsTerminal => {
+                let mut scheme = serde_json::Map::new();
+                scheme.insert("name".to_string(), name.into());
+                for (key, i) in [
+                    ("background", BACKGROUND),
+                    ("foreground", FOREGROUND),
+                    ("cursorColor", CURSOR),
+                    ("selectionBackground", 8),
+                ] {
+                    scheme.insert(key.to_string(), hex(i).into());
+                }
+                for i in 0..16 {
+                    // Windows Terminal calls magenta purple
+                    let ansi = ANSI_NAMES[i % 8].replace("magenta", "purple");
+                    let key = if i < 8 {
+                        ansi
+                    } else {
+                        format!("bright{}", capitalize(&ansi))
+                    };
+                    scheme.insert(key, hex(i).into());
+                }
+                out = serde_json::to_string_pretty(&scheme).unwrap_or_default();
+                out.push('\n');
+            }
+            TerminalFormat::VsCode => {
+                let mut colors = serde_json::Map::new();
+                for (key, i) in [
+                    ("editor.background", BACKGROUND),
+                    ("editor.foreground", FOREGROUND),
+                    ("editorCursor.foreground", CURSOR),
+                    ("editor.selectionBackground", 8),
+                    ("editorLineNumber.foreground", 8),
+                    ("sideBar.background", BACKGROUND),
+                    ("activityBar.background", BACKGROUND),
+                    ("statusBar.background", 0),
+                    ("terminal.background", BACKGROUND),
+                    ("terminal.foreground", FOREGROUND),
+                    ("terminalCursor.foreground", CURSOR),
+                ] {
+                    colors.insert(key.to_string(), hex(i).into());
+                }
+                for i in 0..16 {
+                    let bright = if i < 8 { "" } else { "Bright" };
+                    let key = format!("terminal.ansi{bright}{}", capitalize(ANSI_NAMES[i % 8]));
+                    colors.insert(key, hex(i).into());
+                }
+                let token = |scope: &str, i: usize| serde_json::json!({ "scope": scope, "settings": { "foreground": hex(i) } });
+                let dark = ColorUtil::relative_luminance(self.slots[BACKGROUND]) < 0.2;
+                let theme = serde_json::json!({
+                    "name": name,
+                    "type": if dark { "dark" } else { "light" },
+                    "colors": colors,
+                    "tokenColors": [
+                        token("comment", 8),
+                        token("string", 2),
+                        token("constant", 3),
+                        token("keyword", 5),
+                        token("storage", 5),
+                        token("entity.name.function", 4),
+                        token("entity.name.type", 6),
+                        token("variable", FOREGROUND),
+                        token("invalid", 1),
+                    ],
+                });
+                out = serde_json::to_string_pretty(&theme).unwrap_or_default();
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+fn chroma(lab: &Lab) -> f32 {
+    lab.a.hypot(lab.b)
+}
+
+fn hue_angle(lab: &Lab) -> f32 {
+    lab.b.atan2(lab.a).to_degrees().rem_euclid(360.)
+}
+
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).abs() % 360.;
+    d.min(360. - d)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_by_hue_and_exports() {
+        let colors = [
+            Rgb([20, 20, 24]),
+            Rgb([230, 230, 225]),
+            Rgb([200, 40, 40]),
+            Rgb([255, 120, 120]),
+            Rgb([40, 170, 60]),
+            Rgb([50, 90, 220]),
+            Rgb([220, 200, 40]),
+            Rgb([120, 120, 120]),
+        ];
+        let theme = TerminalTheme::suggest(&colors);
+        assert_eq!(theme.slots[BACKGROUND], Rgb([20, 20, 24]));
+        assert_eq!(theme.slots[FOREGROUND], Rgb([230, 230, 225]));
+        assert_eq!(theme.slots[1], Rgb([200, 40, 40]));
+        assert_eq!(theme.slots[9], Rgb([255, 120, 120]));
+        assert_eq!(theme.slots[2], Rgb([40, 170, 60]));
+        assert_eq!(theme.slots[4], Rgb([50, 90, 220]));
+        assert_eq!(theme.slots[8], Rgb([120, 120, 120]));
+        assert_eq!(TerminalTheme::slot_name(13), "Bright magenta");
+
+        let alacritty = theme.export(TerminalFormat::Alacritty, "Test");
+        assert!(alacritty.contains("[colors.bright]\nblack = \"#787878\"\nred = \"#ff7878\"\n"));
+        let kitty = theme.export(TerminalFormat::Kitty, "Test");
+        assert!(kitty.contains("\ncolor1 #c82828\n"));
+        let xresources = theme.export(TerminalFormat::Xresources, "Test");
+        assert!(xresources.contains("*.background: #141418\n"));
+
+        let wt: serde_json::Value =
+            serde_json::from_str(&theme.export(TerminalFormat::WindowsTerminal, "Test")).unwrap();
+        assert!(wt.get("brightPurple").is_some() && wt.get("magenta").is_none());
+        assert_eq!(wt["red"], "#c82828");
+        let vscode: serde_json::Value =
+            serde_json::from_str(&theme.export(TerminalFormat::VsCode, "Test")).unwrap();
+        assert_eq!(vscode["type"], "dark");
+        assert_eq!(vscode["colors"]["terminal.ansiBrightRed"], "#ff7878");
+    }
+}
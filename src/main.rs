@@ -11,10 +11,15 @@ mod debug;
 mod widgets;
 use apps::WrapApp;
 
-use crate::core::{color::Rgb, color_names::ColorNames};
+use crate::core::{
+    color::{Lab, Rgb},
+    color_names::ColorNames,
+};
 
 lazy_static! {
     static ref COLOR_DICT: Mutex<HashMap<Rgb<u8>, String>> = Mutex::new(HashMap::new());
+    /// The same names with their Lab values, for nearest name lookups.
+    static ref COLOR_LABS: Mutex<Vec<(Lab, String)>> = Mutex::new(Vec::new());
 }
 
 fn main() -> eframe::Result {
@@ -47,6 +52,7 @@ fn init_color_dict() {
 
     match colors_res {
         Ok(map) => {
+            *COLOR_LABS.lock().unwrap() = ColorNames::labs(&map);
             let mut dict = COLOR_DICT.lock().unwrap();
             *dict = map;
         }
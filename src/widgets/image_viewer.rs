@@ -0,0 +1,208 @@
+use egui::{Color32, Pos2, Rect, Response, Sense, Stroke, TextureHandle, Ui, Vec2, pos2};
+use serde::{Deserialize, Serialize};
+
+use crate::core::eyedropper::Eyedropper;
+
+/// How the viewer picks its zoom level.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ZoomMode {
+    /// Whole image fits the viewport.
+    #[default]
+    Fit,
+    /// One source pixel per screen point.
+    Actual,
+    /// Largest whole-number scale that still fits, for crisp pixel art.
+    Integer,
+    /// Set by wheel zoom or panning.
+    Free,
+}
+
+/// Zoom level above which the pixel grid is drawn.
+const GRID_MIN_ZOOM: f32 = 6.;
+const MIN_ZOOM: f32 = 0.02;
+const MAX_ZOOM: f32 = 64.;
+
+/// Zoomable, pannable view of a texture.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImageViewer {
+    pub mode: ZoomMode,
+    pub zoom: f32,
+    /// Pan of the image center away from the viewport center, in points.
+    pub offset: Vec2,
+    pub show_grid: bool,
+}
+
+impl Default for ImageViewer {
+    fn default() -> Self {
+        Self {
+            mode: ZoomMode::Fit,
+            zoom: 1.,
+            offset: Vec2::ZERO,
+            show_grid: false,
+        }
+    }
+}
+
+pub struct ImageViewerResponse {
+    pub response: Response,
+    /// Screen rect covered by the whole image. Usually larger than, or
+    /// clipped by, `response.rect`.
+    pub image_rect: Rect,
+}
+
+impl ImageViewerResponse {
+    /// Painter clipped to the viewport for drawing overlays over the image.
+    pub fn painter(&self, ui: &Ui) -> egui::Painter {
+        ui.painter_at(self.response.rect)
+    }
+
+    pub fn paint_overlay(&self, ui: &Ui, texture: &TextureHandle) {
+        let uv = Rect::from_min_max(pos2(0., 0.), pos2(1., 1.));
+        self.painter(ui)
+            .image(texture.id(), self.image_rect, uv, Color32::WHITE);
+    }
+
+    /// Source pixel under a screen position, if it's inside the viewport and image.
+    pub fn image_pixel(&self, pos: Pos2, img_size: (u32, u32)) -> Option<(u32, u32)> {
+        if !self.response.rect.contains(pos) {
+            return None;
+        }
+        Eyedropper::image_pixel(self.image_rect, pos, img_size)
+    }
+
+    /// Unrounded source image coordinates of a screen position.
+    pub fn image_pos(&self, pos: Pos2, img_size: (u32, u32)) -> (f32, f32) {
+        (
+            (pos.x - self.image_rect.min.x) / self.image_rect.width() * img_size.0 as f32,
+            (pos.y - self.image_rect.min.y) / self.image_rect.height() * img_size.1 as f32,
+        )
+    }
+
+    /// Screen position of source image coordinates.
+    pub fn screen_pos(&self, x: f32, y: f32, img_size: (u32, u32)) -> Pos2 {
+        pos2(
+            self.image_rect.min.x + x / img_size.0 as f32 * self.image_rect.width(),
+            self.image_rect.min.y + y / img_size.1 as f32 * self.image_rect.height(),
+        )
+    }
+}
+
+impl ImageViewer {
+    pub fn controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(self.mode == ZoomMode::Fit, "Fit")
+                .clicked()
+            {
+                self.set_mode(ZoomMode::Fit);
+            }
+            if ui
+                .selectable_label(self.mode == ZoomMode::Actual, "1:1")
+                .clicked()
+            {
+                self.set_mode(ZoomMode::Actual);
+            }
+            if ui
+                .selectable_label(self.mode == ZoomMode::Integer, "Integer")
+                .clicked()
+            {
+                self.set_mode(ZoomMode::Integer);
+            }
+            ui.label(format!("{:.0}%", self.zoom * 100.));
+            ui.checkbox(&mut self.show_grid, "Pixel grid");
+        });
+    }
+
+    fn set_mode(&mut self, mode: ZoomMode) {
+        self.mode = mode;
+        self.offset = Vec2::ZERO;
+    }
+
+    /// Shows the texture in a viewport of `size`. Wheel zooms around the
+    /// pointer, middle drag always pans and primary drag pans when
+    /// `primary_pans` is set.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        texture: &TextureHandle,
+        size: Vec2,
+        primary_pans: bool,
+    ) -> ImageViewerResponse {
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        let img_size = texture.size_vec2();
+        let fit_zoom = f32::min(rect.width() / img_size.x, rect.height() / img_size.y);
+        match self.mode {
+            ZoomMode::Fit => self.zoom = fit_zoom,
+            ZoomMode::Actual => self.zoom = 1.,
+            ZoomMode::Integer => self.zoom = fit_zoom.floor().max(1.),
+            ZoomMode::Free => {}
+        }
+
+        if let Some(pointer) = response.hover_pos() {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            if scroll != 0. {
+                let new_zoom = (self.zoom * (scroll * 0.002).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+                // Keep the image point under the pointer in place
+                let center = rect.center() + self.offset;
+                let image_point = (pointer - center) / self.zoom;
+                self.offset = pointer - rect.center() - image_point * new_zoom;
+                self.zoom = new_zoom;
+                self.mode = ZoomMode::Free;
+            }
+        }
+        let panning = response.dragged_by(egui::PointerButton::Middle)
+            || (primary_pans && response.dragged_by(egui::PointerButton::Primary));
+        if panning {
+            self.offset += response.drag_delta();
+            self.mode = ZoomMode::Free;
+        }
+
+        let image_rect = Rect::from_center_size(rect.center() + self.offset, img_size * self.zoom);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        painter.image(
+            texture.id(),
+            image_rect,
+            Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)),
+            Color32::WHITE,
+        );
+        if self.show_grid && self.zoom >= GRID_MIN_ZOOM {
+            Self::paint_grid(&painter, rect, image_rect, self.zoom);
+        }
+
+        ImageViewerResponse {
+            response,
+            image_rect,
+        }
+    }
+
+    fn paint_grid(painter: &egui::Painter, viewport: Rect, image_rect: Rect, zoom: f32) {
+        let visible = viewport.intersect(image_rect);
+        if !visible.is_positive() {
+            return;
+        }
+        let stroke = Stroke::new(1.0, Color32::from_black_alpha(90));
+        let first_col = ((visible.min.x - image_rect.min.x) / zoom).floor();
+        let last_col = ((visible.max.x - image_rect.min.x) / zoom).ceil();
+        for col in first_col as i32..=last_col as i32 {
+            let x = image_rect.min.x + col as f32 * zoom;
+            painter.line_segment([pos2(x, visible.min.y), pos2(x, visible.max.y)], stroke);
+        }
+        let first_row = ((visible.min.y - image_rect.min.y) / zoom).floor();
+        let last_row = ((visible.max.y - image_rect.min.y) / zoom).ceil();
+        for row in first_row as i32..=last_row as i32 {
+            let y = image_rect.min.y + row as f32 * zoom;
+            painter.line_segment([pos2(visible.min.x, y), pos2(visible.max.x, y)], stroke);
+        }
+    }
+}
+
+/// Texture options for images shown in the viewer: smooth when shrunk,
+/// crisp pixels when zoomed in.
+pub fn viewer_texture_options() -> egui::TextureOptions {
+    egui::TextureOptions {
+        magnification: egui::TextureFilter::Nearest,
+        minification: egui::TextureFilter::Linear,
+        ..Default::default()
+    }
+}
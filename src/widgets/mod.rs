@@ -2,4 +2,5 @@
 pub mod color_picker;
 pub use color_picker::*;
 
-pub mod fixed_cache;
\ No newline at end of file
+pub mod fixed_cache;
+pub mod image_viewer;
\ No newline at end of file